@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use crate::error::{Error, Result, parse_token};
+use crate::solver::Answer;
+
+/// The known answers, keyed by day and part
+pub type Answers = BTreeMap<(usize, usize), Answer>;
+
+pub const DEFAULT_ANSWERS_FILE: &str = "answers.toml";
+
+/// Read a file like the one written by `render`, which is a small subset of TOML:
+/// a `[day<N>]` table per day, with `part<N> = <answer>` entries.
+/// Numeric answers are bare integers and text answers are quoted strings.
+pub fn parse(lines: &[String]) -> Result<Answers> {
+    let mut answers = Answers::new();
+    let mut day = None;
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(table) = trimmed.strip_prefix("[day").and_then(|rest| rest.strip_suffix(']')) {
+            day = Some(parse_token(index, line, table, "a day number")?);
+            continue;
+        }
+        let day = day.ok_or_else(|| Error::parse(index, line, "expected a `[day<N>]` table first"))?;
+        let (key, value) = trimmed
+        .split_once('=')
+        .ok_or_else(|| Error::parse(index, line, "expected `part<N> = <answer>`"))?;
+        let key = key.trim();
+        let part = key
+        .strip_prefix("part")
+        .ok_or_else(|| Error::token(index, line, key, "expected `part<N>`"))?;
+        let part = parse_token(index, line, part, "a part number")?;
+        let answer = parse_answer(index, line, value.trim())?;
+        if answers.insert((day, part), answer).is_some() {
+            return Err(Error::token(index, line, key, format!("day {} part {} appears twice", day, part)));
+        }
+    }
+    Ok(answers)
+}
+
+fn parse_answer(index: usize, line: &str, value: &str) -> Result<Answer> {
+    match value.strip_prefix('"') {
+        Some(quoted) => {
+            let mut text = String::new();
+            let mut chars = quoted.chars();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.as_str().is_empty() => return Ok(Answer::Text(text)),
+                    Some('"') => return Err(Error::token(index, line, chars.as_str(), "unexpected text after the string")),
+                    Some('\\') => match chars.next() {
+                        Some(c @ '"') | Some(c @ '\\') => text.push(c),
+                        _ => return Err(Error::token(index, line, value, "only \\\" and \\\\ can be escaped")),
+                    },
+                    Some(c) => text.push(c),
+                    None => return Err(Error::token(index, line, value, "unterminated string")),
+                }
+            }
+        },
+        None => Ok(Answer::Number(parse_token(index, line, value, "a number or a quoted string")?)),
+    }
+}
+
+/// The contents of an answers file, in the format read by `parse`
+pub fn render(answers: &Answers) -> String {
+    let mut file = String::new();
+    let mut day = None;
+    for (&(answer_day, part), answer) in answers {
+        if day != Some(answer_day) {
+            if day.is_some() {
+                file.push('\n');
+            }
+            file.push_str(&format!("[day{}]\n", answer_day));
+            day = Some(answer_day);
+        }
+        let value = match answer {
+            Answer::Number(number) => number.to_string(),
+            Answer::Text(text) => format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"")),
+        };
+        file.push_str(&format!("part{} = {}\n", part, value));
+    }
+    file
+}
+
+/// How an answer compares with the recorded one
+pub enum Status {
+    Pass,
+    Fail { expected: Answer },
+    /// There is nothing recorded for this part
+    Missing,
+}
+
+pub fn check(answers: &Answers, day: usize, part: usize, answer: &Answer) -> Status {
+    match answers.get(&(day, part)) {
+        Some(expected) if expected == answer => Status::Pass,
+        Some(expected) => Status::Fail { expected: expected.clone() },
+        None => Status::Missing,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut answers = Answers::new();
+        answers.insert((1, 1), Answer::Number(1014624));
+        answers.insert((1, 2), Answer::Number(-3));
+        answers.insert((21, 2), Answer::Text("a,\"b\"\\c".to_string()));
+        let file = render(&answers);
+        assert_eq!("[day1]\npart1 = 1014624\npart2 = -3\n\n[day21]\npart2 = \"a,\\\"b\\\"\\\\c\"\n", file);
+        let lines: Vec<String> = file.lines().map(|s| s.to_string()).collect();
+        assert_eq!(answers, parse(&lines).unwrap());
+    }
+
+    #[test]
+    fn malformed() {
+        let lines: Vec<String> = vec!["[day1]", "part1 = \"abc"].into_iter().map(|s| s.to_string()).collect();
+        assert!(parse(&lines).is_err());
+        assert!(parse(&lines[1..]).is_err());
+    }
+
+}
@@ -0,0 +1,341 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use crate::Grid;
+use crate::error::{Error, Result};
+use crate::grid::{DIRECTIONS, Position};
+
+/// Which numbers of alive neighbours make a dead cell come alive, and which keep an alive cell alive.
+/// Written like `B3/S23`, which is Conway's Game of Life.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    /// Bit n is set if n neighbours make a cell come alive
+    birth: u32,
+    /// Bit n is set if n neighbours keep a cell alive
+    survival: u32,
+}
+
+impl Rule {
+    /// Counts above 31 are ignored
+    pub const fn new(birth: &[usize], survival: &[usize]) -> Rule {
+        Rule { birth: mask(birth), survival: mask(survival) }
+    }
+
+    /// Whether a cell is alive in the next generation
+    pub fn next(&self, alive: bool, neighbours: usize) -> bool {
+        let counts = if alive { self.survival } else { self.birth };
+        neighbours < 32 && counts >> neighbours & 1 == 1
+    }
+}
+
+const fn mask(counts: &[usize]) -> u32 {
+    let mut mask = 0;
+    let mut i = 0;
+    while i < counts.len() {
+        if counts[i] < 32 {
+            mask |= 1 << counts[i];
+        }
+        i += 1;
+    }
+    mask
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(rule: &str) -> Result<Rule> {
+        let counts = |digits: &str| -> Option<u32> {
+            digits.chars().map(|c| c.to_digit(10)).try_fold(0, |mask, digit| Some(mask | 1 << digit?))
+        };
+        rule
+        .strip_prefix('B')
+        .and_then(|rest| rest.split_once("/S"))
+        .and_then(|(birth, survival)| Some(Rule { birth: counts(birth)?, survival: counts(survival)? }))
+        .ok_or_else(|| Error::input(format!("expected a rule like B3/S23, found {:?}", rule)))
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = |mask: u32| -> String {
+            (0..10).filter(|n| mask >> n & 1 == 1).map(|n| n.to_string()).collect()
+        };
+        write!(f, "B{}/S{}", digits(self.birth), digits(self.survival))
+    }
+}
+
+/// How cells are laid out
+pub trait Topology {
+    type Cell: Copy + Eq + Hash;
+
+    /// The cells whose state counts towards the next state of `cell`, not including itself
+    fn neighbours(&self, cell: Self::Cell) -> Vec<Self::Cell>;
+}
+
+/// Topologies with finitely many cells, which can be stored densely
+pub trait Finite: Topology {
+    fn cells(&self) -> Vec<Self::Cell>;
+}
+
+/// Which cells of a square grid are neighbours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// The eight cells around
+    Adjacent,
+    /// The first cell seen in each of the eight directions, skipping holes
+    LineOfSight,
+}
+
+/// The positions of a `Grid`, some of which may be holes that are never alive and are ignored by their neighbours
+pub struct SquareGrid {
+    /// `true` for the positions that are cells, `false` for the holes
+    cells: Grid<bool>,
+    neighbourhood: Neighbourhood,
+}
+
+impl SquareGrid {
+    pub fn new(cells: Grid<bool>, neighbourhood: Neighbourhood) -> SquareGrid {
+        SquareGrid { cells, neighbourhood }
+    }
+}
+
+impl Topology for SquareGrid {
+    type Cell = Position;
+
+    fn neighbours(&self, cell: Position) -> Vec<Position> {
+        DIRECTIONS
+        .iter()
+        .filter_map(|&direction| match self.neighbourhood {
+            Neighbourhood::Adjacent => self.cells.offset(cell, direction).filter(|&position| self.cells[position]),
+            Neighbourhood::LineOfSight => self.cells.ray(cell, direction).find(|&position| self.cells[position]),
+        })
+        .collect()
+    }
+}
+
+impl Finite for SquareGrid {
+    fn cells(&self) -> Vec<Position> {
+        self.cells.iter().filter(|&(_, &cell)| cell).map(|(position, _)| position).collect()
+    }
+}
+
+/// Points of an infinite lattice
+pub trait LatticePoint: Copy + Eq + Hash {
+    /// The points that differ by at most 1 in every coordinate, not including itself
+    fn adjacent(&self) -> Vec<Self>;
+}
+
+/// An infinite lattice of points of type `P`, where each point is a neighbour of those adjacent to it
+pub struct Lattice<P>(PhantomData<P>);
+
+impl<P> Lattice<P> {
+    pub fn new() -> Lattice<P> {
+        Lattice(PhantomData)
+    }
+}
+
+impl<P> Default for Lattice<P> {
+    fn default() -> Lattice<P> {
+        Lattice::new()
+    }
+}
+
+impl<P: LatticePoint> Topology for Lattice<P> {
+    type Cell = P;
+
+    fn neighbours(&self, point: P) -> Vec<P> {
+        point.adjacent()
+    }
+}
+
+/// An infinite grid of hexagons, in axial coordinates (q, r) where the third cube coordinate is -q-r
+pub struct Hex;
+
+impl Hex {
+    /// The six directions, as changes in (q, r)
+    pub const DIRECTIONS: [(isize, isize); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+}
+
+impl Topology for Hex {
+    type Cell = (isize, isize);
+
+    fn neighbours(&self, (q, r): (isize, isize)) -> Vec<(isize, isize)> {
+        Hex::DIRECTIONS.iter().map(|&(dq, dr)| (q + dq, r + dr)).collect()
+    }
+}
+
+/// Behaviour shared by the ways of storing the cells
+pub trait Automaton {
+    type Cell;
+
+    /// Advance one generation, returning whether any cell changed
+    fn step(&mut self) -> bool;
+
+    /// Number of alive cells
+    fn population(&self) -> usize;
+
+    fn is_alive(&self, cell: Self::Cell) -> bool;
+
+    fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    /// Step until nothing changes, returning how many generations that took.
+    /// Loops forever if the automaton never settles.
+    fn stabilize(&mut self) -> usize {
+        let mut generations = 0;
+        while self.step() {
+            generations += 1;
+        }
+        generations
+    }
+}
+
+/// Keeps the state of every cell, which suits small topologies where many cells are alive
+pub struct Dense<T: Finite> {
+    rule: Rule,
+    cells: Vec<T::Cell>,
+    index: HashMap<T::Cell, usize>,
+    /// Indices of the neighbours of each cell
+    neighbours: Vec<Vec<usize>>,
+    alive: Vec<bool>,
+}
+
+impl<T: Finite> Dense<T> {
+    /// Alive cells that aren't part of the topology are ignored
+    pub fn new(topology: &T, rule: Rule, alive: impl IntoIterator<Item=T::Cell>) -> Dense<T> {
+        let cells = topology.cells();
+        let index: HashMap<T::Cell, usize> = cells.iter().enumerate().map(|(i, &cell)| (cell, i)).collect();
+        let neighbours = cells
+        .iter()
+        .map(|&cell| topology.neighbours(cell).iter().filter_map(|neighbour| index.get(neighbour).cloned()).collect())
+        .collect();
+        let mut state = vec![false; cells.len()];
+        for cell in alive {
+            if let Some(&i) = index.get(&cell) {
+                state[i] = true;
+            }
+        }
+        Dense { rule, cells, index, neighbours, alive: state }
+    }
+
+    pub fn alive(&self) -> impl Iterator<Item=T::Cell> + '_ {
+        self.cells.iter().zip(self.alive.iter()).filter(|&(_, &alive)| alive).map(|(&cell, _)| cell)
+    }
+}
+
+impl<T: Finite> Automaton for Dense<T> {
+    type Cell = T::Cell;
+
+    fn step(&mut self) -> bool {
+        let next: Vec<bool> = self.neighbours
+        .iter()
+        .zip(self.alive.iter())
+        .map(|(neighbours, &alive)| self.rule.next(alive, neighbours.iter().filter(|&&i| self.alive[i]).count()))
+        .collect();
+        let changed = next != self.alive;
+        self.alive = next;
+        changed
+    }
+
+    fn population(&self) -> usize {
+        self.alive.iter().filter(|&&alive| alive).count()
+    }
+
+    fn is_alive(&self, cell: T::Cell) -> bool {
+        self.index.get(&cell).map_or(false, |&i| self.alive[i])
+    }
+}
+
+/// Keeps only the alive cells, which suits infinite topologies
+pub struct Sparse<T: Topology> {
+    topology: T,
+    rule: Rule,
+    alive: HashSet<T::Cell>,
+}
+
+impl<T: Topology> Sparse<T> {
+    /// Panics if the rule makes cells with no alive neighbours come alive, since there would be infinitely many
+    pub fn new(topology: T, rule: Rule, alive: impl IntoIterator<Item=T::Cell>) -> Sparse<T> {
+        assert!(!rule.next(false, 0), "{} can't be stored sparsely", rule);
+        Sparse { topology, rule, alive: alive.into_iter().collect() }
+    }
+
+    pub fn alive(&self) -> &HashSet<T::Cell> {
+        &self.alive
+    }
+}
+
+impl<T: Topology> Automaton for Sparse<T> {
+    type Cell = T::Cell;
+
+    fn step(&mut self) -> bool {
+        // Only the alive cells and their neighbours can be alive in the next generation
+        let mut counts: HashMap<T::Cell, usize> = self.alive.iter().map(|&cell| (cell, 0)).collect();
+        for &cell in &self.alive {
+            for neighbour in self.topology.neighbours(cell) {
+                *counts.entry(neighbour).or_insert(0) += 1;
+            }
+        }
+        let next: HashSet<T::Cell> = counts
+        .into_iter()
+        .filter(|&(cell, neighbours)| self.rule.next(self.alive.contains(&cell), neighbours))
+        .map(|(cell, _)| cell)
+        .collect();
+        let changed = next != self.alive;
+        self.alive = next;
+        changed
+    }
+
+    fn population(&self) -> usize {
+        self.alive.len()
+    }
+
+    fn is_alive(&self, cell: T::Cell) -> bool {
+        self.alive.contains(&cell)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LIFE: Rule = Rule::new(&[3], &[2, 3]);
+
+    #[test]
+    fn rules() {
+        assert_eq!(LIFE, "B3/S23".parse().unwrap());
+        assert_eq!("B0/S0123", Rule::new(&[0], &[0, 1, 2, 3]).to_string());
+        assert!(LIFE.next(false, 3) && LIFE.next(true, 2) && !LIFE.next(true, 4) && !LIFE.next(false, 2));
+        assert!("B3S23".parse::<Rule>().is_err());
+        assert!("B3/Sx".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn blinker() {
+        let grid = SquareGrid::new(Grid::filled(5, 5, true), Neighbourhood::Adjacent);
+        let horizontal = vec![(1, 2), (2, 2), (3, 2)];
+        let vertical = vec![(2, 1), (2, 2), (2, 3)];
+        let mut dense = Dense::new(&grid, LIFE, horizontal.clone());
+        let mut sparse = Sparse::new(grid, LIFE, horizontal);
+        dense.step();
+        sparse.step();
+        assert_eq!(vertical, dense.alive().collect::<Vec<_>>());
+        assert_eq!(vertical.into_iter().collect::<HashSet<_>>(), *sparse.alive());
+        assert!(dense.step() && sparse.step());
+        assert!(dense.is_alive((1, 2)) && sparse.is_alive((1, 2)));
+    }
+
+    #[test]
+    fn line_of_sight() {
+        let cells = Grid::new(4, 1, vec![true, false, false, true]);
+        assert!(SquareGrid::new(cells.clone(), Neighbourhood::Adjacent).neighbours((0, 0)).is_empty());
+        assert_eq!(vec![(3, 0)], SquareGrid::new(cells, Neighbourhood::LineOfSight).neighbours((0, 0)));
+    }
+
+}
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result, parse_token};
+use crate::runner::format_duration;
+use crate::solver::Puzzle;
+
+pub const DEFAULT_WARMUP: usize = 3;
+pub const DEFAULT_RUNS: usize = 10;
+
+/// How much slower than the baseline a part has to be for it to be flagged, in percent
+pub const DEFAULT_THRESHOLD: f64 = 10.0;
+
+/// Summary of the times taken by repeated runs of the same phase
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Panics if there are no samples
+    pub fn new(samples: &[Duration]) -> Stats {
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let n = sorted.len();
+        let median = if n % 2 == 0 { (sorted[n / 2 - 1] + sorted[n / 2]) / 2 } else { sorted[n / 2] };
+
+        let nanos: Vec<f64> = sorted.iter().map(|sample| sample.as_nanos() as f64).collect();
+        let mean = nanos.iter().sum::<f64>() / n as f64;
+        let variance = nanos.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / n as f64;
+
+        Stats {
+            min: sorted[0],
+            median,
+            mean: Duration::from_nanos(mean as u64),
+            stddev: Duration::from_nanos(variance.sqrt() as u64),
+        }
+    }
+}
+
+/// Timings of one part of a day
+pub struct Measurement {
+    pub day: usize,
+    pub part: usize,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+impl Measurement {
+    /// What gets compared against the baseline
+    pub fn median(&self) -> Duration {
+        self.parse.median + self.solve.median
+    }
+}
+
+/// Parse the input and solve the given part `warmup` times without measuring, then `runs` times measuring each phase.
+/// `runs` must be at least 1.
+pub fn measure(puzzle: &dyn Puzzle, part: usize, lines: &[String], warmup: usize, runs: usize) -> Result<Measurement> {
+    let mut parse = vec![];
+    let mut solve = vec![];
+    for run in 0..warmup + runs {
+        let lines = lines.to_vec();
+        let start = Instant::now();
+        let input = puzzle.parse(lines)?;
+        let parsed = start.elapsed();
+
+        let start = Instant::now();
+        puzzle.solve(part, input.as_ref())?;
+        let solved = start.elapsed();
+
+        if run >= warmup {
+            parse.push(parsed);
+            solve.push(solved);
+        }
+    }
+    Ok(Measurement { day: puzzle.day(), part, parse: Stats::new(&parse), solve: Stats::new(&solve) })
+}
+
+/// Median time of each day and part in a previous run
+pub type Baseline = BTreeMap<(usize, usize), Duration>;
+
+/// One `<day> <part> <nanoseconds>` line per part
+pub fn parse_baseline(lines: &[String]) -> Result<Baseline> {
+    let mut baseline = Baseline::new();
+    for (index, line) in lines.iter().enumerate().filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#')) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (key, median) = match fields.as_slice() {
+            [day, part, nanos] => (
+                (parse_token(index, line, day, "a day")?, parse_token(index, line, part, "a part")?),
+                Duration::from_nanos(parse_token(index, line, nanos, "a number of nanoseconds")?),
+            ),
+            _ => return Err(Error::parse(index, line, "expected `<day> <part> <nanoseconds>`")),
+        };
+        if baseline.insert(key, median).is_some() {
+            return Err(Error::parse(index, line, format!("day {} part {} is already in the baseline", key.0, key.1)));
+        }
+    }
+    Ok(baseline)
+}
+
+/// The contents of a baseline file, in the format read by `parse_baseline`
+pub fn render_baseline(measurements: &[Measurement]) -> String {
+    let mut file = "# day part median-nanoseconds\n".to_string();
+    for measurement in measurements {
+        file.push_str(&format!("{} {} {}\n", measurement.day, measurement.part, measurement.median().as_nanos()));
+    }
+    file
+}
+
+/// Relative change with respect to the baseline, in percent
+pub fn change(measurement: &Measurement, baseline: Duration) -> f64 {
+    let baseline = baseline.as_nanos().max(1) as f64;
+    (measurement.median().as_nanos() as f64 - baseline) / baseline * 100.0
+}
+
+/// Print the statistics of each part and, if there is a baseline, how they compare with it.
+/// Returns how many parts are slower than the baseline by more than `threshold` percent.
+pub fn print_table(measurements: &[Measurement], baseline: Option<&Baseline>, threshold: f64) -> usize {
+    let header = format!(
+        "{:>3} {:>4}  {:^43}  {:^43}  {}",
+        "", "", "parse", "solve", if baseline.is_some() { "vs baseline" } else { "" },
+    );
+    println!("{}", header.trim_end());
+    println!(
+        "{:>3} {:>4}  {:>10} {:>10} {:>10} {:>10}  {:>10} {:>10} {:>10} {:>10}",
+        "day", "part", "min", "median", "mean", "stddev", "min", "median", "mean", "stddev",
+    );
+    let mut slower = 0;
+    for measurement in measurements {
+        let comparison = match baseline.map(|baseline| baseline.get(&(measurement.day, measurement.part))) {
+            None => String::new(),
+            Some(None) => "  not in baseline".to_string(),
+            Some(Some(&median)) => {
+                let change = change(measurement, median);
+                if change > threshold {
+                    slower += 1;
+                    format!("  {:+.1}% SLOWER", change)
+                } else {
+                    format!("  {:+.1}%", change)
+                }
+            },
+        };
+        println!(
+            "{:>3} {:>4}  {:>10} {:>10} {:>10} {:>10}  {:>10} {:>10} {:>10} {:>10}{}",
+            measurement.day,
+            measurement.part,
+            format_duration(measurement.parse.min),
+            format_duration(measurement.parse.median),
+            format_duration(measurement.parse.mean),
+            format_duration(measurement.parse.stddev),
+            format_duration(measurement.solve.min),
+            format_duration(measurement.solve.median),
+            format_duration(measurement.solve.mean),
+            format_duration(measurement.solve.stddev),
+            comparison,
+        );
+    }
+    slower
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let samples: Vec<Duration> = [4, 1, 3, 2].iter().map(|&ms| Duration::from_millis(ms)).collect();
+        let stats = Stats::new(&samples);
+        assert_eq!(Duration::from_millis(1), stats.min);
+        assert_eq!(Duration::from_micros(2500), stats.median);
+        assert_eq!(Duration::from_micros(2500), stats.mean);
+        assert_eq!(1118033, stats.stddev.as_nanos());
+    }
+
+    #[test]
+    fn baseline() {
+        let stats = Stats::new(&[Duration::from_nanos(100)]);
+        let measurements = vec![Measurement { day: 5, part: 2, parse: stats, solve: stats }];
+        let lines: Vec<String> = render_baseline(&measurements).lines().map(|s| s.to_string()).collect();
+        let baseline = parse_baseline(&lines).unwrap();
+        assert_eq!(Some(&Duration::from_nanos(200)), baseline.get(&(5, 2)));
+        assert_eq!(0.0, change(&measurements[0], Duration::from_nanos(200)));
+        assert_eq!(100.0, change(&measurements[0], Duration::from_nanos(100)));
+        let lines: Vec<String> = vec!["5 2 100".to_string(), "# again".to_string(), "5 2 300".to_string()];
+        assert!(parse_baseline(&lines).unwrap_err().to_string().starts_with("<input>:3: day 5 part 2 is already in the baseline"));
+    }
+
+}
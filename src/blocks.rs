@@ -0,0 +1,103 @@
+use crate::error::{Error, Result};
+
+/// A run of lines that aren't blank, as found by `read_blocks`
+#[derive(Debug, Clone, Copy)]
+pub struct Block<'a> {
+    /// 0-based position among the other blocks
+    pub section: usize,
+    /// 0-based index of the first line in the input
+    pub start: usize,
+    lines: &'a [String],
+}
+
+impl<'a> Block<'a> {
+    /// Each line with its 0-based index in the input, without trailing whitespace
+    pub fn lines(&self) -> impl Iterator<Item=(usize, &'a str)> + Clone {
+        let start = self.start;
+        self.lines.iter().enumerate().map(move |(i, line)| (start + i, line.trim_end()))
+    }
+
+    /// The first line, which is often a title like `Player 1:`
+    pub fn header(&self) -> (usize, &'a str) {
+        (self.start, self.lines[0].trim_end())
+    }
+
+    /// Every line but the header
+    pub fn body(&self) -> impl Iterator<Item=(usize, &'a str)> + Clone {
+        self.lines().skip(1)
+    }
+
+    /// Fail unless the header is exactly `header`
+    pub fn expect_header(&self, header: &str) -> Result<()> {
+        match self.header() {
+            (_, line) if line == header => Ok(()),
+            (index, line) => Err(Error::parse(index, line, format!("expected `{}`", header))),
+        }
+    }
+}
+
+/// Split the input into the blocks separated by blank lines.
+/// Lines with only whitespace count as blank, e.g. a stray `\r`,
+/// and there may be any number of them between blocks, before the first one and after the last one.
+pub fn read_blocks(lines: &[String]) -> impl Iterator<Item=Block<'_>> {
+    let mut start = 0;
+    let mut section = 0;
+    std::iter::from_fn(move || {
+        start += lines[start..].iter().take_while(|line| line.trim().is_empty()).count();
+        if start == lines.len() {
+            return None;
+        }
+        let length = lines[start..].iter().take_while(|line| !line.trim().is_empty()).count();
+        let block = Block { section, start, lines: &lines[start..start + length] };
+        start += length;
+        section += 1;
+        Some(block)
+    })
+}
+
+/// Same as `read_blocks`, for inputs made of one block per name in `sections`, in that order.
+/// The names are only used to point out which sections are missing or unexpected.
+pub fn read_sections<'a>(lines: &'a [String], sections: &[&str]) -> Result<Vec<Block<'a>>> {
+    let blocks: Vec<Block> = read_blocks(lines).collect();
+    if let Some(extra) = blocks.get(sections.len()) {
+        let (index, line) = extra.header();
+        return Err(Error::parse(index, line, format!("expected {} sections, found another one", sections.len())));
+    }
+    if let Some(missing) = sections.get(blocks.len()) {
+        return Err(Error::input(format!("missing {}", missing)));
+    }
+    Ok(blocks)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn blocks() {
+        let lines: Vec<String> = "\na\nb \n\r\n \n\nc\r".lines().map(|s| s.to_string()).collect();
+        let blocks: Vec<Block> = read_blocks(&lines).collect();
+        assert_eq!(2, blocks.len());
+        assert_eq!((0, 1, 2), (blocks[0].section, blocks[0].start, blocks[0].lines().count()));
+        assert_eq!(vec![(1, "a"), (2, "b")], blocks[0].lines().collect::<Vec<_>>());
+        assert_eq!((1, 6), (blocks[1].section, blocks[1].start));
+        assert_eq!((6, "c"), blocks[1].header());
+        assert_eq!(0, blocks[1].body().count());
+        assert_eq!(0, read_blocks(&[]).count());
+    }
+
+    #[test]
+    fn sections() {
+        let lines: Vec<String> = "a\n\nb\n\nc".lines().map(|s| s.to_string()).collect();
+        assert!(read_sections(&lines, &["a", "b", "c"]).is_ok());
+        match read_sections(&lines, &["a", "b"]) {
+            Err(Error::Parse { line, .. }) => assert_eq!(5, line),
+            _ => panic!("expected a parse error"),
+        }
+        match read_sections(&lines, &["a", "b", "c", "the last one"]) {
+            Err(Error::Input { message, .. }) => assert_eq!("missing the last one", message),
+            _ => panic!("expected an input error"),
+        }
+    }
+
+}
@@ -1,73 +1,78 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use crate::error::{Error, Result, parse_token};
+use crate::solver::{Answer, Example, Solver};
 
 const SUM: u32 = 2020;
-const INPUT_FILE: &str = "input/1.txt";
 
-pub fn run1() -> Option<u32> {
+pub struct Day1;
 
-    // Read the input file into a vector
-    let mut numbers: Vec<u32> = vec![];
-    if let Ok(file) = File::open(INPUT_FILE) {
-        for line in io::BufReader::new(file).lines() {
-            if let Ok(line) = line {
-                if let Ok(number) = line.parse::<u32>() {
-                    numbers.push(number);
-                }
-            }
-        }
+impl Solver for Day1 {
+    type Input = Vec<u32>;
+    const DAY: usize = 1;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: EXAMPLE, answers: [Some("514579"), Some("241861950")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<u32>> {
+        parse(lines)
+    }
+
+    fn part1(&self, numbers: &Vec<u32>) -> Result<Answer> {
+        find_two(numbers).map(Answer::from).ok_or(Error::NoSolution)
+    }
+
+    fn part2(&self, numbers: &Vec<u32>) -> Result<Answer> {
+        find_three(numbers).map(Answer::from).ok_or(Error::NoSolution)
     }
+}
+
+/// Read the input lines into a sorted vector
+pub fn parse(lines: Vec<String>) -> Result<Vec<u32>> {
+    let mut numbers: Vec<u32> = lines
+    .iter()
+    .enumerate()
+    .map(|(index, line)| parse_token(index, line, line, "an expense"))
+    .collect::<Result<_>>()?;
 
-    // Sort it
     numbers.sort_unstable();
+    Ok(numbers)
+}
+
+pub fn find_two(numbers: &[u32]) -> Option<u32> {
 
     // Now look for the combinations:
-    let mut i = 1;
-    for first_number in &numbers {
-        for second_number in &numbers[i..] {
+    for (i, first_number) in numbers.iter().enumerate() {
+        for second_number in &numbers[i + 1..] {
             if first_number + second_number == SUM {
                 return Some(first_number * second_number);
             }
         }
-        i += 1;
     }
 
     None
 
 }
 
-pub fn run2() -> Option<u32> {
-
-    // Read the input file into a vector
-    let mut numbers: Vec<u32> = vec![];
-    if let Ok(file) = File::open(INPUT_FILE) {
-        for line in io::BufReader::new(file).lines() {
-            if let Ok(line) = line {
-                if let Ok(number) = line.parse::<u32>() {
-                    numbers.push(number);
-                }
-            }
-        }
-    }
-
-    // Sort it
-    numbers.sort_unstable();
+pub fn find_three(numbers: &[u32]) -> Option<u32> {
 
     // Now look for the combinations:
-    let mut i = 1;
-    for first_number in &numbers {
-        let mut j = 2;
-        for second_number in &numbers[i..] {
-            for third_number in &numbers[j..] {
+    for (i, first_number) in numbers.iter().enumerate() {
+        for (j, second_number) in numbers.iter().enumerate().skip(i + 1) {
+            for third_number in &numbers[j + 1..] {
                 if first_number + second_number + third_number == SUM {
                     return Some(first_number * second_number * third_number);
                 }
             }
-            j += 1;
         }
-        i += 1;
     }
 
     None
 
 }
+
+const EXAMPLE: &str =
+"1721
+979
+366
+299
+675
+1456";
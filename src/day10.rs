@@ -1,27 +1,56 @@
 use std::iter;
 
-use crate::lib;
+use crate::error::{Error, Result, parse_token};
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day10;
+
+impl Solver for Day10 {
+    /// Joltage ratings of the adapters
+    type Input = Vec<u64>;
+    const DAY: usize = 10;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("35"), Some("8")] },
+        Example { input: INPUT_2, answers: [Some("220"), Some("19208")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<u64>> {
+        lines.iter().enumerate().map(|(index, line)| parse_token(index, line, line, "a joltage")).collect()
+    }
 
-pub const INPUT_FILE: &str = "input/10.txt";
+    fn part1(&self, numbers: &Vec<u64>) -> Result<Answer> {
+        check_chain(numbers)?;
+        process1(numbers.clone()).map(Answer::from).ok_or(Error::NoSolution)
+    }
 
-fn differences(lines: Vec<String>) -> Vec<u64> {
-    let mut numbers = lines
-    .into_iter()
-    .map(|l| l.parse::<u64>().unwrap())
-    .collect::<Vec<u64>>();
+    fn part2(&self, numbers: &Vec<u64>) -> Result<Answer> {
+        check_chain(numbers)?;
+        Ok(process2(numbers.clone()).into())
+    }
+}
 
+/// Every adapter must be usable, i.e. all the differences must be 1, 2 or 3 jolts
+pub fn check_chain(numbers: &[u64]) -> Result<()> {
+    if differences(numbers.to_vec()).iter().all(|difference| (1..=3).contains(difference)) {
+        Ok(())
+    } else {
+        Err(Error::NoSolution)
+    }
+}
+
+pub fn differences(mut numbers: Vec<u64>) -> Vec<u64> {
     numbers.sort_unstable();
-    let &max = numbers.iter().max().unwrap();
+    let &max = numbers.iter().max().unwrap_or(&0);
 
-    let curs = iter::once(0).chain(numbers.clone().into_iter());
+    let curs = iter::once(0).chain(numbers.clone());
     let nexts = numbers.into_iter().chain(iter::once(max + 3));
 
     curs.zip(nexts).map(|(cur, next)| next - cur).collect()
 }
 
-fn process1(lines: Vec<String>) -> Option<usize> {
+pub fn process1(numbers: Vec<u64>) -> Option<usize> {
 
-    let differences = differences(lines);
+    let differences = differences(numbers);
 
     let _1s = differences.clone().into_iter().filter(|&d| d == 1).count();
     let _3s = differences.into_iter().filter(|&d| d == 3).count();
@@ -50,14 +79,14 @@ fn process1(lines: Vec<String>) -> Option<usize> {
 //                           [3, 3, 1] }
 // This can be done recursively.
 
-fn combinations(chunk: Vec<u64>) -> u64 {
+pub fn combinations(chunk: Vec<u64>) -> u64 {
     if chunk.len() <= 1 {
         return 1;
     }
     let mut sum: u64 = 0;
     sum += combinations(chunk.clone().into_iter().skip(1).collect());
     if chunk[0] + chunk[1] <= 3 {
-        if let Some(first) = chunk.get(0) {
+        if let Some(first) = chunk.first() {
             if let Some(second) = chunk.get(1) {
                 if first + second <= 3 {
                     let mut smaller_chunk = vec![first + second];
@@ -70,28 +99,27 @@ fn combinations(chunk: Vec<u64>) -> u64 {
     sum
 }
 
-fn process2(lines: Vec<String>) -> u64 {
+pub fn process2(numbers: Vec<u64>) -> u64 {
 
-    let differences = differences(lines);
+    let differences = differences(numbers);
 
     let string = differences
     .into_iter()
 
     // easiest way to split by 3s? convert into a string, apparently
-    .map(|n| n.to_string().chars().nth(0).unwrap())
+    .map(|n| n.to_string().chars().next().unwrap())
     .collect::<String>();
 
     let chunks = string.split('3');
 
     chunks
-    .map(|chunk| {
+    .flat_map(|chunk| {
         chunk
         .replace("22", "2X")
         .split('X')
         .map(|s| s.to_string())
         .collect::<Vec<String>>()
     })
-    .flatten()
     .map(|s|
         s
         .chars()
@@ -103,25 +131,12 @@ fn process2(lines: Vec<String>) -> u64 {
         )
         .collect::<Vec<u64>>()
     )
-    .map(|chunk| combinations(chunk))
+    .map(combinations)
     .product::<u64>()
 
 }
 
-pub fn run1(file: &str) -> Option<usize> {
-    process1(lib::read_lines(file).ok()?.collect())
-}
-
-pub fn run2(file: &str) -> std::io::Result<u64> {
-    Ok(process2(lib::read_lines(file)?.collect()))
-}
-
-#[cfg(test)]
-mod test {
-    use super::{process1, process2};
-
-
-    const INPUT_1: &str =
+const INPUT_1: &str =
 "16
 10
 15
@@ -134,7 +149,7 @@ mod test {
 12
 4";
 
-    const INPUT_2: &str =
+const INPUT_2: &str =
 "28
 33
 18
@@ -167,24 +182,28 @@ mod test {
 10
 3";
 
+#[cfg(test)]
+mod test {
+    use super::{process1, process2, INPUT_1, INPUT_2};
+
     #[test]
     fn run1_1() {
-        assert_eq!(Some(35), process1(INPUT_1.lines().map(|s| s.to_string()).collect()));
+        assert_eq!(Some(35), process1(INPUT_1.lines().map(|s| s.parse().unwrap()).collect()));
     }
 
     #[test]
     fn run1_2() {
-        assert_eq!(Some(220), process1(INPUT_2.lines().map(|s| s.to_string()).collect()));
+        assert_eq!(Some(220), process1(INPUT_2.lines().map(|s| s.parse().unwrap()).collect()));
     }
 
     #[test]
     fn run2_1() {
-        assert_eq!(8, process2(INPUT_1.lines().map(|s| s.to_string()).collect()));
+        assert_eq!(8, process2(INPUT_1.lines().map(|s| s.parse().unwrap()).collect()));
     }
 
     #[test]
     fn run2_2() {
-        assert_eq!(19208, process2(INPUT_2.lines().map(|s| s.to_string()).collect()));
+        assert_eq!(19208, process2(INPUT_2.lines().map(|s| s.parse().unwrap()).collect()));
     }
 
 }
@@ -1,131 +1,63 @@
-use std::iter;
+use crate::Grid;
+use crate::automaton::{Automaton, Dense, Neighbourhood, Rule, SquareGrid};
+use crate::error::Result;
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day11;
+
+impl Solver for Day11 {
+    type Input = Grid<char>;
+    const DAY: usize = 11;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("37"), Some("26")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Grid<char>> {
+        parse(&lines)
+    }
 
-use crate::lib;
+    fn part1(&self, grid: &Grid<char>) -> Result<Answer> {
+        Ok(count_occupied(&stabilize(grid.clone(), true)).into())
+    }
 
-pub const INPUT_FILE: &str = "input/11.txt";
+    fn part2(&self, grid: &Grid<char>) -> Result<Answer> {
+        Ok(count_occupied(&stabilize(grid.clone(), false)).into())
+    }
+}
 
 const FLOOR: char = '.';
 const FREE: char = 'L';
 const OCCUPIED: char = '#';
 
-fn next1(grid: &Vec<Vec<char>>) -> Vec<Vec<char>> {
-    let mut next_grid: Vec<Vec<char>> = grid.clone();
-    for (i, row) in grid.iter().enumerate() {
-        for (j, &value) in row.iter().enumerate() {
-            if value == FLOOR {
-                continue;
-            }
-            let horizontal = if i == 0 { vec![i, i+1] } else if i == next_grid.len() - 1 { vec![i-1, i] } else { vec![i-1, i, i+1] };
-            let vertical = if j == 0 { vec![j, j+1] } else if j == row.len() - 1 { vec![j-1, j] } else { vec![j-1, j, j+1] };
-            let neighbors_with_center = horizontal.iter().flat_map(|i_| iter::repeat(i_).zip(vertical.iter()));
-            let neighbors = neighbors_with_center.filter(|(&i_, &j_)| (i, j) != (i_, j_));
-            next_grid[i][j] = match neighbors.filter(|(&i_, &j_)| grid[i_][j_] == OCCUPIED).count() {
-                0 => OCCUPIED,
-                4..=8 => FREE,
-                _ => value,
-            }
-        }
-    };
-
-    next_grid
+pub fn parse(lines: &[String]) -> Result<Grid<char>> {
+    Grid::parse(lines, |c| Some(c).filter(|c| [FLOOR, FREE, OCCUPIED].contains(c)))
 }
 
-fn next2(grid: &Vec<Vec<char>>) -> Vec<Vec<char>> {
-    let mut next_grid: Vec<Vec<char>> = grid.clone();
-    for (i, row) in grid.iter().enumerate() {
-        for (j, &value) in row.iter().enumerate() {
-            if value == FLOOR {
-                continue;
-            }
-
-            let top_i = (0..i).rev();
-            let middle_i = iter::repeat(i);
-            let bottom_i = (i..next_grid.len()).skip(1);
-
-            let left_j = (0..j).rev();
-            let middle_j = iter::repeat(j);
-            let right_j = (j..row.len()).skip(1);
-
-            // For each arm of the "windmill", we want to go from the center outside, and skip the center
-            let tl = top_i.clone().zip(left_j.clone());
-            let t = top_i.clone().zip(middle_j.clone());
-            let tr = top_i.clone().zip(right_j.clone());
-            let r = middle_i.clone().zip(right_j.clone());
-            let br = bottom_i.clone().zip(right_j.clone());
-            let b = bottom_i.clone().zip(middle_j.clone());
-            let bl = bottom_i.clone().zip(left_j.clone());
-            let l = middle_i.clone().zip(left_j.clone());
-
-            let windmill: Vec<Vec<(usize, usize)>> = vec![
-                tl.collect(),
-                t.collect(),
-                tr.collect(),
-                r.collect(),
-                br.collect(),
-                b.collect(),
-                bl.collect(),
-                l.collect()
-            ];
-
-            let total_occupied = windmill.into_iter().filter(|line| {
-                match line.iter()
-                .map(|(i_, j_)| grid[*i_][*j_])
-                .filter(|value| *value != FLOOR)
-                .next() {
-                    Some(OCCUPIED) => true,
-                    _ => false,
-                }
-            }).count();
-
-            next_grid[i][j] = match total_occupied {
-                0 => OCCUPIED,
-                5..=8 => FREE,
-                _ => value,
-            }
-        }
-    };
-
-    next_grid
+/// A seat becomes occupied if no seat it looks at is, and becomes free if enough of them are
+const RULE_1: Rule = Rule::new(&[0], &[0, 1, 2, 3]);
+const RULE_2: Rule = Rule::new(&[0], &[0, 1, 2, 3, 4]);
+
+/// Seats are the cells, and the floor is made of holes.
+/// In the first part seats look at the eight seats around them,
+/// and in the second at the first seat they can see in each of the eight directions.
+pub fn stabilize(grid: Grid<char>, first: bool) -> Grid<char> {
+    let neighbourhood = if first { Neighbourhood::Adjacent } else { Neighbourhood::LineOfSight };
+    let seats = SquareGrid::new(grid.map(|&value| value != FLOOR), neighbourhood);
+    let occupied = grid.iter().filter(|&(_, &value)| value == OCCUPIED).map(|(position, _)| position);
+    let mut automaton = Dense::new(&seats, if first { RULE_1 } else { RULE_2 }, occupied);
+    automaton.stabilize();
+    Grid::from_fn(grid.width(), grid.height(), |position| match grid[position] {
+        FLOOR => FLOOR,
+        _ if automaton.is_alive(position) => OCCUPIED,
+        _ => FREE,
+    })
 }
 
-fn next(grid: &Vec<Vec<char>>, first: bool) -> Vec<Vec<char>> {
-    if first {
-        next1(grid)
-    } else {
-        next2(grid)
-    }
+pub fn count_occupied(grid: &Grid<char>) -> usize {
+    grid.iter().filter(|&(_, &value)| value == OCCUPIED).count()
 }
 
-fn stabilize(grid: Vec<Vec<char>>, first: bool) -> Vec<Vec<char>> {
-    let mut current_grid = grid;
-    let mut next_grid = next(&current_grid, first);
-    while next_grid != current_grid {
-        current_grid = next_grid;
-        next_grid = next(&current_grid, first);
-    }
-    next_grid
-}
-
-fn count_occupied(grid: &Vec<Vec<char>>) -> usize {
-    grid.iter().flatten().filter(|&&value| value == OCCUPIED).count()
-}
-
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    let grid: Vec<Vec<char>> = lib::read_lines(file)?.map(|s| s.chars().collect::<Vec<char>>()).collect();
-    Ok(count_occupied(&stabilize(grid, true)))
-}
-
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    let grid: Vec<Vec<char>> = lib::read_lines(file)?.map(|s| s.chars().collect::<Vec<char>>()).collect();
-    Ok(count_occupied(&stabilize(grid, false)))
-}
-
-#[cfg(test)]
-mod test {
-    use super::{stabilize, count_occupied};
-
-
-    const INPUT_1: &str =
+const INPUT_1: &str =
 "L.LL.LL.LL
 LLLLLLL.LL
 L.L.L..L..
@@ -137,15 +69,21 @@ LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL";
 
+#[cfg(test)]
+mod test {
+    use super::{stabilize, count_occupied, parse, INPUT_1};
+
     #[test]
     fn run1() {
-        let grid: Vec<Vec<char>> = INPUT_1.lines().map(|s| s.chars().collect::<Vec<char>>()).collect();
+        let lines: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        let grid = parse(&lines).unwrap();
         assert_eq!(37, count_occupied(&stabilize(grid, true)));
     }
 
     #[test]
     fn run2() {
-        let grid: Vec<Vec<char>> = INPUT_1.lines().map(|s| s.chars().collect::<Vec<char>>()).collect();
+        let lines: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        let grid = parse(&lines).unwrap();
         assert_eq!(26, count_occupied(&stabilize(grid, false)));
     }
 
@@ -1,13 +1,36 @@
 pub mod part1;
 pub mod part2;
 
-pub const INPUT_FILE: &str = "input/12.txt";
+use crate::error::{Error, Result, parse_token};
+use crate::solver::{Answer, Example, Solver};
 
-fn manhattan_distance((x, y): (isize, isize)) -> usize {
+pub struct Day12;
+
+impl Solver for Day12 {
+    type Input = Vec<Instruction>;
+    const DAY: usize = 12;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: EXAMPLE, answers: [Some("25"), Some("286")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<Instruction>> {
+        parse_instructions(&lines)
+    }
+
+    fn part1(&self, instructions: &Vec<Instruction>) -> Result<Answer> {
+        Ok(part1::run(instructions).into())
+    }
+
+    fn part2(&self, instructions: &Vec<Instruction>) -> Result<Answer> {
+        Ok(part2::run(instructions).into())
+    }
+}
+
+pub fn manhattan_distance((x, y): (isize, isize)) -> usize {
     (x.abs() + y.abs()) as usize
 }
 
-enum Letter {
+pub enum Letter {
     N,
     S,
     E,
@@ -17,33 +40,48 @@ enum Letter {
     F,
 }
 
-struct Instruction {
-    letter: Letter,
-    argument: usize,
+pub struct Instruction {
+    pub letter: Letter,
+    pub argument: usize,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Direction {
+pub enum Direction {
     North,
     East,
     South,
     West,
 }
 
-fn parse_instruction(line: &String) -> Instruction {
-    let mut iter = line.chars().clone();
-    let letter: Letter = match iter.next().unwrap() {
-        'N' => Letter::N,
-        'S' => Letter::S,
-        'E' => Letter::E,
-        'W' => Letter::W,
-        'L' => Letter::L,
-        'R' => Letter::R,
-        _ => Letter::F,
+pub fn parse_instruction(index: usize, line: &str) -> Result<Instruction> {
+    let mut iter = line.chars();
+    let letter: Letter = match iter.next() {
+        Some('N') => Letter::N,
+        Some('S') => Letter::S,
+        Some('E') => Letter::E,
+        Some('W') => Letter::W,
+        Some('L') => Letter::L,
+        Some('R') => Letter::R,
+        Some('F') => Letter::F,
+        _ => return Err(Error::parse(index, line, "expected one of N, S, E, W, L, R or F").at(Some(1))),
     };
-    let argument: usize = iter.collect::<String>().parse::<usize>().unwrap();
-    Instruction {
+    let argument: usize = parse_token(index, line, iter.as_str(), "a number")?;
+    if matches!(letter, Letter::L | Letter::R) && argument % 90 != 0 {
+        return Err(Error::token(index, line, iter.as_str(), "the ship can only turn in multiples of 90 degrees"));
+    }
+    Ok(Instruction {
         letter,
         argument,
-    }
+    })
 }
+
+pub fn parse_instructions(lines: &[String]) -> Result<Vec<Instruction>> {
+    lines.iter().enumerate().map(|(index, line)| parse_instruction(index, line)).collect()
+}
+
+const EXAMPLE: &str =
+"F10
+N3
+F7
+R90
+F11";
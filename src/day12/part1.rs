@@ -1,6 +1,4 @@
-use crate::lib;
-
-use super::{Direction, Letter, manhattan_distance, parse_instruction};
+use super::{Direction, Instruction, Letter, manhattan_distance};
 
 #[derive(Debug, Clone, Copy)]
 struct Ship {
@@ -36,7 +34,7 @@ fn rotate_l(direction: Direction, times: usize) -> Direction {
     result
 }
 
-fn destination(instructions: Vec<String>) -> (isize, isize) {
+pub fn destination(instructions: &[Instruction]) -> (isize, isize) {
 
     let ship = Ship {
         position: (0, 0),
@@ -44,9 +42,8 @@ fn destination(instructions: Vec<String>) -> (isize, isize) {
     };
 
     instructions.iter()
-    .map(|line| parse_instruction(&line))
     .fold(ship, |ship, instruction| {
-        let mut new_ship = ship.clone();
+        let mut new_ship = ship;
         match instruction.letter {
             Letter::N => {
                 new_ship.position = (new_ship.position.0, new_ship.position.1 + instruction.argument as isize);
@@ -82,14 +79,14 @@ fn destination(instructions: Vec<String>) -> (isize, isize) {
 
 }
 
-pub fn run(file: &str) -> std::io::Result<usize> {
-    let instructions: Vec<String> = lib::read_lines(file)?.collect();
-    Ok(manhattan_distance(destination(instructions)))
+pub fn run(instructions: &[Instruction]) -> usize {
+    manhattan_distance(destination(instructions))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use super::super::{parse_instructions, EXAMPLE};
 
     #[test]
     fn rotate_r_1_4() {
@@ -100,17 +97,10 @@ mod test {
         assert_eq!(Direction::East, rotate_r(Direction::North, 4001));
     }
 
-    const INPUT_1: &str =
-"F10
-N3
-F7
-R90
-F11";
-
     #[test]
     fn run() {
-        let instructions: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
-        assert_eq!(25, manhattan_distance(destination(instructions)));
+        let instructions: Vec<String> = EXAMPLE.lines().map(|s| s.to_string()).collect();
+        assert_eq!(25, manhattan_distance(destination(&parse_instructions(&instructions).unwrap())));
     }
 
 }
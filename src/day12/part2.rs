@@ -1,6 +1,4 @@
-use crate::lib;
-
-use super::{Letter, manhattan_distance, parse_instruction};
+use super::{Instruction, Letter, manhattan_distance};
 
 #[derive(Debug, Clone, Copy)]
 struct Ship {
@@ -21,7 +19,7 @@ fn rotate_l(ship: Ship, times: usize) -> Ship {
     rotate_r(ship, 4 - (times % 4))
 }
 
-fn destination(instructions: Vec<String>) -> (isize, isize) {
+pub fn destination(instructions: &[Instruction]) -> (isize, isize) {
 
     let ship = Ship {
         position: (0, 0),
@@ -29,7 +27,6 @@ fn destination(instructions: Vec<String>) -> (isize, isize) {
     };
 
     instructions.iter()
-    .map(|line| parse_instruction(&line))
     .fold(ship, |mut ship, instruction| {
         match instruction.letter {
             Letter::N => {
@@ -63,26 +60,19 @@ fn destination(instructions: Vec<String>) -> (isize, isize) {
 
 }
 
-pub fn run(file: &str) -> std::io::Result<usize> {
-    let instructions: Vec<String> = lib::read_lines(file)?.collect();
-    Ok(manhattan_distance(destination(instructions)))
+pub fn run(instructions: &[Instruction]) -> usize {
+    manhattan_distance(destination(instructions))
 }
 
 #[cfg(test)]
 mod test {
     use super::{destination, manhattan_distance};
-
-    const INPUT: &str =
-"F10
-N3
-F7
-R90
-F11";
+    use super::super::{parse_instructions, EXAMPLE};
 
     #[test]
     fn run() {
-        let instructions: Vec<String> = INPUT.lines().map(|s| s.to_string()).collect();
-        assert_eq!(286, manhattan_distance(destination(instructions)));
+        let instructions: Vec<String> = EXAMPLE.lines().map(|s| s.to_string()).collect();
+        assert_eq!(286, manhattan_distance(destination(&parse_instructions(&instructions).unwrap())));
     }
 
 }
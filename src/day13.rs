@@ -1,11 +1,43 @@
-use crate::lib;
+use crate::error::{Error, Result, parse_token};
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day13;
+
+impl Solver for Day13 {
+    /// Earliest departure, and the bus ids with None for the x's
+    type Input = (usize, Vec<Option<usize>>);
+    const DAY: usize = 13;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("295"), Some("1068781")] },
+        Example { input: "939\n17,x,13,19", answers: [None, Some("3417")] },
+        Example { input: "939\n67,7,59,61", answers: [None, Some("754018")] },
+        Example { input: "939\n67,x,7,59,61", answers: [None, Some("779210")] },
+        Example { input: "939\n67,7,x,59,61", answers: [None, Some("1261476")] },
+        Example { input: "939\n1789,37,47,1889", answers: [None, Some("1202161486")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<(usize, Vec<Option<usize>>)> {
+        parse_input(lines)
+    }
 
-pub const INPUT_FILE: &str = "input/13.txt";
+    fn part1(&self, (earliest, list): &(usize, Vec<Option<usize>>)) -> Result<Answer> {
+        let bus_ids = bus_ids(list);
+        if bus_ids.is_empty() {
+            return Err(Error::NoSolution);
+        }
+        let times = times(earliest, &bus_ids);
+        Ok((times.0 * times.1).into())
+    }
+
+    fn part2(&self, (_, list): &(usize, Vec<Option<usize>>)) -> Result<Answer> {
+        times2(list.clone()).map(Answer::from).ok_or(Error::NoSolution)
+    }
+}
 
 /// Returns (earliest bus id you can take, waiting time)
-fn times(earliest: &usize, bus_ids: &Vec<usize>) -> (usize, usize) {
+pub fn times(earliest: &usize, bus_ids: &[usize]) -> (usize, usize) {
     for time in *earliest.. {
-        for &bus_id in &bus_ids[..] {
+        for &bus_id in bus_ids {
             if time % bus_id == 0 {
                 return (bus_id, time - earliest);
             }
@@ -15,35 +47,36 @@ fn times(earliest: &usize, bus_ids: &Vec<usize>) -> (usize, usize) {
     (0,0)
 }
 
-fn parse_input(lines: Vec<String>) -> Option<(usize, Vec<usize>)> {
-    Some((
-        lines.get(0)?.parse::<usize>().ok()?,
-        lines
-        .get(1)?
-        .split(',')
-        .filter(|&s| s != "x")
-        .try_fold(vec![], |mut acc, cur| {
-            acc.push(cur.parse().ok()?);
-            Some(acc)
-        })?
-    ))
+pub fn parse_input(lines: Vec<String>) -> Result<(usize, Vec<Option<usize>>)> {
+    match lines.as_slice() {
+        [earliest, list, ..] => Ok((
+            parse_token(0, earliest, earliest, "a timestamp")?,
+            parse_list(1, list)?,
+        )),
+        _ => Err(Error::input("expected a timestamp and a list of buses")),
+    }
 }
 
-pub fn run1(file: &str) -> Option<usize> {
-    let lines: Vec<String> = lib::read_lines(file).ok()?.collect();
-    let (earliest, bus_ids) = parse_input(lines).unwrap();
-    let times = times(&earliest, &bus_ids);
-    Some(times.0 * times.1)
+/// The buses in service, skipping the x's
+pub fn bus_ids(list: &[Option<usize>]) -> Vec<usize> {
+    list.iter().flatten().cloned().collect()
 }
 
-fn parse_list(list: String) -> Vec<Option<usize>> {
+/// Comma-separated list of bus ids, or x for the buses out of service
+pub fn parse_list(index: usize, list: &str) -> Result<Vec<Option<usize>>> {
     list
     .split(',')
-    .map(|s| s.parse::<usize>().ok())
+    .map(|s| match s {
+        "x" => Ok(None),
+        _ => match parse_token(index, list, s, "a bus id or x")? {
+            0 => Err(Error::token(index, list, s, "bus ids must be positive")),
+            id => Ok(Some(id)),
+        },
+    })
     .collect()
 }
 
-fn euclides(a: isize, b: isize) -> (isize, isize, isize) {
+pub fn euclides(a: isize, b: isize) -> (isize, isize, isize) {
     if a == 0 {
         (b, 0, 1)
     } else {
@@ -52,7 +85,7 @@ fn euclides(a: isize, b: isize) -> (isize, isize, isize) {
     }
 }
  
-fn inverse(x: isize, m: isize) -> Option<isize> {
+pub fn inverse(x: isize, m: isize) -> Option<isize> {
     let (gcd, x, _) = euclides(x, m);
     if gcd == 1 {
         Some((x % m + m) % m)
@@ -61,7 +94,7 @@ fn inverse(x: isize, m: isize) -> Option<isize> {
     }
 }
  
-fn chinese_remainder(list: Vec<(isize, isize)>) -> Option<isize> {
+pub fn chinese_remainder(list: Vec<(isize, isize)>) -> Option<isize> {
 
     let prod: isize = list.iter().map(|(_, modulus)| modulus).product();
 
@@ -74,12 +107,12 @@ fn chinese_remainder(list: Vec<(isize, isize)>) -> Option<isize> {
 
 }
 
-fn times2(input: Vec<Option<usize>>) -> Option<isize> {
+pub fn times2(input: Vec<Option<usize>>) -> Option<isize> {
 
     let list: Vec<(isize, isize)> = input
     .into_iter()
     .enumerate()
-    .filter(|(_, option)| *option != None)
+    .filter(|(_, option)| option.is_some())
     .map(|(i, some)| {
         let value = some.unwrap() as isize;
         (value - i as isize, value)
@@ -90,24 +123,19 @@ fn times2(input: Vec<Option<usize>>) -> Option<isize> {
 
 }
 
-pub fn run2(file: &str) -> Option<isize> {
-    let lines: Vec<String> = lib::read_lines(file).ok()?.collect();
-    times2(parse_list(lines[1].clone()))
-}
+const INPUT_1: &str =
+"939
+7,13,x,x,59,x,31,19";
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const INPUT_1: &str =
-"939
-7,13,x,x,59,x,31,19";
-
     #[test]
     fn run1() {
         let lines: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
-        let (earliest, bus_ids) = parse_input(lines).unwrap();
-        let times = times(&earliest, &bus_ids);
+        let (earliest, list) = parse_input(lines).unwrap();
+        let times = times(&earliest, &bus_ids(&list));
         assert_eq!(295, times.0 * times.1);
     }
 
@@ -122,7 +150,7 @@ mod test {
             ("1789,37,47,1889", 1202161486),
         ];
         for (input, output) in cases.iter() {
-            assert_eq!(Some(*output as isize), times2(parse_list(input.to_string())));
+            assert_eq!(Some(*output as isize), times2(parse_list(0, input).unwrap()));
         }
     }
 
@@ -1,11 +1,36 @@
 use std::collections::BTreeMap;
 
-use crate::lib;
+use crate::error::{Error, Result, parse_token};
+use crate::solver::{Answer, Example, Solver};
 
-pub const INPUT_FILE: &str = "input/14.txt";
+/// Width of the memory words and addresses
+const BITS: usize = 36;
 
-#[derive(Debug)]
-enum Instruction {
+pub struct Day14;
+
+impl Solver for Day14 {
+    type Input = Vec<Instruction>;
+    const DAY: usize = 14;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("165"), None] },
+        Example { input: INPUT_2, answers: [None, Some("208")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<Instruction>> {
+        parse_lines(&lines)
+    }
+
+    fn part1(&self, instructions: &Vec<Instruction>) -> Result<Answer> {
+        run(instructions, false).map(Answer::from).ok_or(Error::NoSolution)
+    }
+
+    fn part2(&self, instructions: &Vec<Instruction>) -> Result<Answer> {
+        run(instructions, true).map(Answer::from).ok_or(Error::NoSolution)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
     SetMask {
         mask: String
     },
@@ -20,23 +45,45 @@ struct ProgramState {
     memory: BTreeMap<String, String>,
 }
 
-fn parse_line(line: String) -> Option<Instruction> {
-    if line.starts_with("mask = ") {
-        Some(Instruction::SetMask {
-            mask: line[7..].to_string()
+pub fn parse_lines(lines: &[String]) -> Result<Vec<Instruction>> {
+    lines.iter().enumerate().map(|(index, line)| parse_line(index, line)).collect()
+}
+
+pub fn parse_line(index: usize, line: &str) -> Result<Instruction> {
+    if let Some(mask) = line.strip_prefix("mask = ") {
+        if let Some((column, c)) = mask.chars().enumerate().find(|(_, c)| !"01X".contains(*c)) {
+            return Err(Error::token(index, line, mask, format!("unexpected {:?} in the mask", c))
+                .at(Some("mask = ".len() + column + 1)));
+        }
+        if mask.len() != BITS {
+            return Err(Error::token(index, line, mask, format!("the mask must be {} bits long", BITS)));
+        }
+        Ok(Instruction::SetMask {
+            mask: mask.to_string()
         })
     } else {
         lazy_static! {
             static ref WRITE_REGEX: regex::Regex = regex::Regex::new(r"^mem\[(\d+)\] = (\d+)$").unwrap();
         }
-        let captures = WRITE_REGEX.captures_iter(&line).next()?;
-        Some(Instruction::Write {
-            address: format!("{:036b}", captures[1].parse::<usize>().ok()?),
-            value: format!("{:036b}", captures[2].parse::<usize>().ok()?),
+        let captures = WRITE_REGEX
+        .captures(line)
+        .ok_or_else(|| Error::parse(index, line, "expected `mask = <mask>` or `mem[<address>] = <value>`"))?;
+        Ok(Instruction::Write {
+            address: parse_word(index, line, &captures[1], "an address")?,
+            value: parse_word(index, line, &captures[2], "a value")?,
         })
     }
 }
 
+/// A decimal number that must fit in the memory, as a string of bits
+fn parse_word(index: usize, line: &str, token: &str, what: &str) -> Result<String> {
+    let word: u64 = parse_token(index, line, token, what)?;
+    if word >> BITS != 0 {
+        return Err(Error::token(index, line, token, format!("{} must fit in {} bits", what, BITS)));
+    }
+    Ok(format!("{:036b}", word))
+}
+
 fn parse_integer(integer: &str) -> Option<usize> {
     usize::from_str_radix(integer, 2).ok()
 }
@@ -98,56 +145,53 @@ fn apply_v2(state: ProgramState, instruction: Instruction) -> ProgramState {
     }
 }
 
-fn run(lines: impl Iterator<Item=String>, version2: bool) -> Option<usize> {
+pub fn run(instructions: &[Instruction], version2: bool) -> Option<usize> {
     let state = ProgramState {
         mask: "XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX".to_string(),
         memory: BTreeMap::new(),
     };
 
-    lines
-    .map(parse_line)
-    .try_fold(state, |state, instruction| Some(if version2 {
-        apply_v2(state, instruction?)
+    instructions
+    .iter()
+    .cloned()
+    .fold(state, |state, instruction| if version2 {
+        apply_v2(state, instruction)
     } else {
-        apply_v1(state, instruction?)
-    }))?
+        apply_v1(state, instruction)
+    })
     .memory
     .values()
     .try_fold(0, |acc, cur| Some(acc + parse_integer(cur)?))
 }
 
-pub fn run1(file: &str) -> Option<usize> {
-    run(lib::read_lines(file).ok()?, false)
-}
+const INPUT_1: &str =
+"mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
+mem[8] = 11
+mem[7] = 101
+mem[8] = 0";
 
-pub fn run2(file: &str) -> Option<usize> {
-    run(lib::read_lines(file).ok()?, true)
-}
+const INPUT_2: &str =
+"mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1";
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const INPUT_1: &str =
-"mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
-mem[8] = 11
-mem[7] = 101
-mem[8] = 0";
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(|s| s.to_string()).collect()
+    }
 
     #[test]
     fn run1() {
-        assert_eq!(Some(165), run(INPUT_1.lines().map(|s| s.to_string()), false));
+        assert_eq!(Some(165), run(&parse_lines(&lines(INPUT_1)).unwrap(), false));
     }
 
-    const INPUT_2: &str =
-"mask = 000000000000000000000000000000X1001X
-mem[42] = 100
-mask = 00000000000000000000000000000000X0XX
-mem[26] = 1";
-
     #[test]
     fn run2() {
-        assert_eq!(Some(208), run(INPUT_2.lines().map(|s| s.to_string()), true));
+        assert_eq!(Some(208), run(&parse_lines(&lines(INPUT_2)).unwrap(), true));
     }
 
 }
@@ -1,15 +1,44 @@
 use std::collections::HashMap;
 
-use crate::lib;
+use crate::error::{Error, Result, parse_token};
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day15;
+
+impl Solver for Day15 {
+    /// Starting numbers
+    type Input = Vec<usize>;
+    const DAY: usize = 15;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: "0,3,6", answers: [Some("436"), Some("175594")] },
+        Example { input: "1,3,2", answers: [Some("1"), Some("2578")] },
+        Example { input: "2,1,3", answers: [Some("10"), Some("3544142")] },
+        Example { input: "1,2,3", answers: [Some("27"), Some("261214")] },
+        Example { input: "2,3,1", answers: [Some("78"), Some("6895259")] },
+        Example { input: "3,2,1", answers: [Some("438"), Some("18")] },
+        Example { input: "3,1,2", answers: [Some("1836"), Some("362")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<usize>> {
+        parse(lines.first().ok_or_else(|| Error::input("expected a list of starting numbers"))?)
+    }
+
+    fn part1(&self, starting: &Vec<usize>) -> Result<Answer> {
+        Ok(get_2020(Game::from_starting(starting.clone())).into())
+    }
 
-pub const INPUT_FILE: &str = "input/15.txt";
+    fn part2(&self, starting: &Vec<usize>) -> Result<Answer> {
+        Ok(get_30000000(Game::from_starting(starting.clone())).into())
+    }
+}
 
-fn parse(input: String) -> Vec<usize> {
-    input.split(',').map(|s| s.parse().unwrap()).collect()
+/// Comma-separated starting numbers, at least one
+pub fn parse(input: &str) -> Result<Vec<usize>> {
+    input.split(',').map(|s| parse_token(0, input, s, "a starting number")).collect()
 }
 
 #[derive(Debug)]
-struct Game {
+pub struct Game {
     /// Maps each number to the last position it appeared in
     memory: HashMap<usize, usize>,
     /// Last position and number, not yet in memory
@@ -18,11 +47,11 @@ struct Game {
 }
 
 impl Game {
-    fn from_starting(starting: Vec<usize>) -> impl Iterator<Item=usize> {
+    pub fn from_starting(starting: Vec<usize>) -> impl Iterator<Item=usize> {
         let number = *starting.last().unwrap();
         let position = starting.len() - 1;
         let game = Game {
-            memory: starting[..position].into_iter().enumerate().fold(HashMap::new(), |mut memory, (position, number)| {
+            memory: starting[..position].iter().enumerate().fold(HashMap::new(), |mut memory, (position, number)| {
                 memory.insert(*number, position);
                 memory
             }),
@@ -55,24 +84,14 @@ impl Iterator for Game {
     }
 }
 
-fn get_2020(mut sequence: impl Iterator<Item=usize>) -> usize {
+pub fn get_2020(mut sequence: impl Iterator<Item=usize>) -> usize {
     sequence.nth(2020 - 1).unwrap()
 }
 
-fn get_30000000(mut sequence: impl Iterator<Item=usize>) -> usize {
+pub fn get_30000000(mut sequence: impl Iterator<Item=usize>) -> usize {
     sequence.nth(30_000_000 - 1).unwrap()
 }
 
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    let line = lib::read_lines(file)?.next().unwrap();
-    Ok(get_2020(Game::from_starting(parse(line))))
-}
-
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    let line = lib::read_lines(file)?.next().unwrap();
-    Ok(get_30000000(Game::from_starting(parse(line))))
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -89,7 +108,7 @@ mod test {
             ("3,1,2", 1836),
         ];
         for &(input, output) in cases.iter() {
-            assert_eq!(output, get_2020(Game::from_starting(parse(input.to_string()))));
+            assert_eq!(output, get_2020(Game::from_starting(parse(input).unwrap())));
         }
     }
 
@@ -105,7 +124,7 @@ mod test {
             ("3,1,2", 362),
         ];
         for &(input, output) in cases.iter() {
-            assert_eq!(output, get_30000000(Game::from_starting(parse(input.to_string()))));
+            assert_eq!(output, get_30000000(Game::from_starting(parse(input).unwrap())));
         }
     }
 
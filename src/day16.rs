@@ -1,68 +1,102 @@
 use std::{collections::HashMap, ops::RangeInclusive};
 
-use crate::lib;
+use crate::error::{Error, Result, parse_token};
+use crate::read_sections;
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day16;
+
+impl Solver for Day16 {
+    type Input = Notes;
+    const DAY: usize = 16;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("71"), None] },
+        // No field starts with "departure", so part 2 multiplies no values
+        Example { input: INPUT_2, answers: [Some("0"), Some("1")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Notes> {
+        contents_to_notes(&lines)
+    }
+
+    fn part1(&self, notes: &Notes) -> Result<Answer> {
+        Ok(error_rate(notes.clone()).into())
+    }
 
-pub const INPUT_FILE: &str = "input/16.txt";
+    fn part2(&self, notes: &Notes) -> Result<Answer> {
+        let columns = fields_order(notes.clone()).ok_or(Error::NoSolution)?;
+        Ok(multiply_departures(notes.1.clone(), columns).into())
+    }
+}
 
-type Fields = HashMap<String, (RangeInclusive<usize>, RangeInclusive<usize>)>;
+/// The two ranges a field's values can be in
+pub type Field = (RangeInclusive<usize>, RangeInclusive<usize>);
 
-type Notes = (
+pub type Fields = HashMap<String, Field>;
+
+pub type Notes = (
     Fields, // Fields
     Vec<usize>, // Your ticket
     Vec<Vec<usize>>, // Nearby tickets
 );
 
-/// Given a iterator of lines of the input file, returns:
+/// Given the lines of the input file, returns:
 /// - fields
 /// - your ticket
 /// - nearby tickets
-fn contents_to_notes(mut contents: impl Iterator<Item=String>) -> Notes {
+pub fn contents_to_notes(contents: &[String]) -> Result<Notes> {
 
-    // Fields
-    let fields = contents
-    .by_ref()
-    .take_while(|line| line != "")
-    .fold(HashMap::new(), |mut fields, line| {
-        let pieces: Vec<_> = line.split(':').collect();
-        let name = pieces[0];
-        let mut rules = pieces[1].split(" or ").map(|rule| {
-            let ends: Vec<usize> = rule.split('-').map(|end| end.trim().parse().unwrap()).collect();
-            ends[0]..=ends[1]
-        });
-        fields.insert(name.to_string(), (rules.next().unwrap(), rules.next().unwrap()));
-
-        fields
-    });
+    let sections = read_sections(contents, &["the fields", "your ticket", "the nearby tickets"])?;
 
-    // Skip "your ticket:" line
-    contents.by_ref().next();
+    // Fields
+    let mut fields = HashMap::new();
+    for (index, line) in sections[0].lines() {
+        let (name, field) = parse_field(index, line)?;
+        fields.insert(name, field);
+    }
 
-    // Your tickets
-    let your_ticket: Vec<usize> = contents
-    .by_ref()
-    .next()
-    .unwrap()
-    .split(',')
-    .map(|number| number.parse().unwrap())
-    .collect();
+    // "your ticket:" line and your ticket
+    sections[1].expect_header("your ticket:")?;
+    let your_ticket = match sections[1].body().collect::<Vec<_>>().as_slice() {
+        &[(index, line)] => parse_ticket(index, line)?,
+        _ => {
+            let (index, line) = sections[1].header();
+            return Err(Error::parse(index, line, "expected your ticket in the next line, and only that"));
+        },
+    };
+
+    // "nearby tickets:" line and nearby tickets
+    sections[2].expect_header("nearby tickets:")?;
+    let nearby_tickets = sections[2].body().map(|(index, line)| {
+        let ticket = parse_ticket(index, line)?;
+        if ticket.len() != your_ticket.len() {
+            return Err(Error::parse(index, line, format!("expected {} numbers like your ticket", your_ticket.len())));
+        }
+        Ok(ticket)
+    }).collect::<Result<_>>()?;
 
-    // Skip empty line and "nearby tickets:" line
-    contents.by_ref().next();
-    contents.by_ref().next();
+    Ok((fields, your_ticket, nearby_tickets))
 
-    // Nearby tickets
-    let nearby_tickets: Vec<Vec<usize>> = contents.map(|line| {
-        line
-        .split(',')
-        .map(|number| number.parse().unwrap())
-        .collect()
-    }).collect();
+}
 
-    (fields, your_ticket, nearby_tickets)
+/// e.g. `class: 1-3 or 5-7`
+fn parse_field(index: usize, line: &str) -> Result<(String, Field)> {
+    let expected = || Error::parse(index, line, "expected `<field>: <from>-<to> or <from>-<to>`");
+    let (name, rules) = line.split_once(':').ok_or_else(expected)?;
+    let (rule0, rule1) = rules.split_once(" or ").ok_or_else(expected)?;
+    let rule = |rule: &str| -> Result<RangeInclusive<usize>> {
+        let (from, to) = rule.split_once('-').ok_or_else(expected)?;
+        Ok(parse_token(index, line, from.trim(), "a number")?..=parse_token(index, line, to.trim(), "a number")?)
+    };
+    Ok((name.to_string(), (rule(rule0)?, rule(rule1)?)))
+}
 
+/// Comma-separated numbers
+fn parse_ticket(index: usize, line: &str) -> Result<Vec<usize>> {
+    line.split(',').map(|number| parse_token(index, line, number, "a number")).collect()
 }
 
-fn all_rules<'a>(fields: &'a Fields) -> Vec<&'a RangeInclusive<usize>> {
+fn all_rules(fields: &Fields) -> Vec<&RangeInclusive<usize>> {
     fields
     .values()
     .fold(vec![], |mut rules, (rule0, rule1)| {
@@ -72,7 +106,7 @@ fn all_rules<'a>(fields: &'a Fields) -> Vec<&'a RangeInclusive<usize>> {
     })
 }
 
-fn valid_number(number: usize, rules: &Vec<&RangeInclusive<usize>>) -> bool {
+fn valid_number(number: usize, rules: &[&RangeInclusive<usize>]) -> bool {
     rules
     .iter()
     .any(|rule| {
@@ -80,7 +114,7 @@ fn valid_number(number: usize, rules: &Vec<&RangeInclusive<usize>>) -> bool {
     }) 
 }
 
-fn error_rate((fields, _, nearby_tickets): Notes) -> usize {
+pub fn error_rate((fields, _, nearby_tickets): Notes) -> usize {
 
     let all_rules = all_rules(&fields);
 
@@ -98,13 +132,8 @@ fn error_rate((fields, _, nearby_tickets): Notes) -> usize {
 
 }
 
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    let input = lib::read_lines(file)?;
-    Ok(error_rate(contents_to_notes(input)))
-}
-
-fn valid_ticket(ticket: &Vec<usize>, rules: &Vec<&RangeInclusive<usize>>) -> bool {
-    ticket.into_iter().all(|number| valid_number(*number, rules))
+fn valid_ticket(ticket: &[usize], rules: &[&RangeInclusive<usize>]) -> bool {
+    ticket.iter().all(|number| valid_number(*number, rules))
 }
 
 fn transpose(source: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
@@ -113,7 +142,8 @@ fn transpose(source: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
     .collect()
 }
 
-fn fields_order((fields, your_ticket, nearby_tickets): Notes) -> Vec<String> {
+/// None if the fields can't be told apart
+pub fn fields_order((fields, your_ticket, nearby_tickets): Notes) -> Option<Vec<String>> {
 
     let all_rules = all_rules(&fields);
     let valid_tickets: Vec<Vec<usize>> = nearby_tickets.clone()
@@ -139,7 +169,7 @@ fn fields_order((fields, your_ticket, nearby_tickets): Notes) -> Vec<String> {
         let filtered_columns = columns
         .iter()
         .enumerate()
-        .filter(|(_, column)| valid_ticket(*column, &vec![&rules.0, &rules.1]))
+        .filter(|(_, column)| valid_ticket(column, &[&rules.0, &rules.1]))
         .map(|(index, _)| index)
         .collect::<Vec<_>>();
         (name, filtered_columns)
@@ -151,8 +181,8 @@ fn fields_order((fields, your_ticket, nearby_tickets): Notes) -> Vec<String> {
 
     // 3. Find a field that only corresponds to one column. Remove the field, and the column from the other fields, and repeat.
     let mut solution_map: HashMap<String, usize> = HashMap::new();
-    while fields_columns.len() > 0 {
-        let (name, columns) = fields_columns.clone().into_iter().find(|(_, columns)| columns.len() == 1).unwrap();
+    while !fields_columns.is_empty() {
+        let (name, columns) = fields_columns.clone().into_iter().find(|(_, columns)| columns.len() == 1)?;
         solution_map.insert(name.clone(), columns[0]);
 
         // Remove solved field
@@ -172,14 +202,14 @@ fn fields_order((fields, your_ticket, nearby_tickets): Notes) -> Vec<String> {
 
     solution_vec.sort_unstable();
 
-    solution_vec
+    Some(solution_vec
     .into_iter()
     .map(|(_, name)| name)
-    .collect()
+    .collect())
 
 }
 
-fn multiply_departures(your_ticket: Vec<usize>, columns: Vec<String>) -> usize {
+pub fn multiply_departures(your_ticket: Vec<usize>, columns: Vec<String>) -> usize {
     let indices = columns
     .into_iter()
     .enumerate()
@@ -190,19 +220,7 @@ fn multiply_departures(your_ticket: Vec<usize>, columns: Vec<String>) -> usize {
     .product()
 }
 
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    let input = lib::read_lines(file)?;
-    let (fields, your_ticket, nearby_tickets) = contents_to_notes(input);
-    let columns = fields_order((fields, your_ticket.clone(), nearby_tickets));
-    Ok(multiply_departures(your_ticket, columns))
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-
-
-    const INPUT_1: &str =
+const INPUT_1: &str =
 "class: 1-3 or 5-7
 row: 6-11 or 33-44
 seat: 13-40 or 45-50
@@ -216,13 +234,7 @@ nearby tickets:
 55,2,20
 38,6,12";
 
-    #[test]
-    fn run1() {
-        let input = INPUT_1.lines().map(|s| s.to_string());
-        assert_eq!(71, error_rate(contents_to_notes(input)));
-    }
-
-    const INPUT_2: &str =
+const INPUT_2: &str =
 "class: 0-1 or 4-19
 row: 0-5 or 8-19
 seat: 0-13 or 16-19
@@ -235,12 +247,22 @@ nearby tickets:
 15,1,5
 5,14,9";
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn run1() {
+        let input: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        assert_eq!(71, error_rate(contents_to_notes(&input).unwrap()));
+    }
+
     #[test]
     fn run2() {
         // the first position must be row, the second position must be class, and the third position must be seat
-        let input = INPUT_2.lines().map(|s| s.to_string());
+        let input: Vec<String> = INPUT_2.lines().map(|s| s.to_string()).collect();
         let correct = vec!["row", "class", "seat"];
-        let mine = fields_order(contents_to_notes(input));
+        let mine = fields_order(contents_to_notes(&input).unwrap()).unwrap();
         assert_eq!(correct, mine);
     }
 
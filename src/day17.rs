@@ -1,174 +1,327 @@
-use std::{collections::{HashMap, HashSet}, hash::Hash};
-
-use crate::lib;
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use crate::Grid;
+use crate::automaton::{Automaton, Lattice, LatticePoint, Rule, Sparse};
+use crate::error::Result;
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day17;
+
+impl Solver for Day17 {
+    /// The initial slice, to be lifted into 3 or 4 dimensions (or up to 6 with `simulate`), `true` where the cube is active
+    type Input = Grid<bool>;
+    const DAY: usize = 17;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("112"), Some("848")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Grid<bool>> {
+        parse(&lines)
+    }
 
-pub const INPUT_FILE: &str = "input/17.txt";
+    fn part1(&self, slice: &Grid<bool>) -> Result<Answer> {
+        Ok(run(lift::<3>(slice), CYCLES).into())
+    }
 
-trait NCube {
-    fn neighborhood(&self) -> Vec<Self>
-    where Self: Sized;
+    fn part2(&self, slice: &Grid<bool>) -> Result<Answer> {
+        Ok(run(lift::<4>(slice), CYCLES).into())
+    }
 }
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone)]
-struct Cube(isize, isize, isize);
-
-impl NCube for Cube {
-    /// Returns the 3*3*3 slice centered around self
-    fn neighborhood(&self) -> Vec<Cube> {
-        let &Cube(x, y, z) = self;
-        let xs = [x-1, x, x+1];
-        let ys = [y-1, y, y+1];
-        let zs = [z-1, z, z+1];
-
-        let mut neighbors = vec![];
-        for &x in xs.iter() {
-            for &y in ys.iter() {
-                for &z in zs.iter() {
-                    neighbors.push(Cube(x, y, z));
-                }
-            }
-        }
+/// A cube of the N-dimensional pocket dimension, with coordinates x, y, z, w...
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Point<const N: usize>(pub [isize; N]);
+
+impl<const N: usize> Point<N> {
+    /// A cube of the initial slice, where every coordinate but x and y is 0
+    pub fn on_slice(x: isize, y: isize) -> Point<N> {
+        let mut coordinates = [0; N];
+        coordinates[0] = x;
+        coordinates[1] = y;
+        Point(coordinates)
+    }
 
-        neighbors
+    /// Starting from a slice, cubes that only differ in the signs or the order of the coordinates past x and y
+    /// are always in the same state. This is the one among them whose extra coordinates are non-negative and sorted.
+    pub fn canonical(&self) -> Point<N> {
+        let mut coordinates = self.0;
+        for coordinate in coordinates[2..].iter_mut() {
+            *coordinate = coordinate.abs();
+        }
+        coordinates[2..].sort_unstable();
+        Point(coordinates)
+    }
 
+    /// How many cubes have this one as their canonical cube, which must be itself
+    pub fn multiplicity(&self) -> usize {
+        let factorial = |n: usize| (1..=n).product::<usize>();
+        let extra = &self.0[2..];
+        // The coordinates are sorted, so equal ones come in runs, and swapping within a run changes nothing
+        let mut orders = factorial(extra.len());
+        let mut start = 0;
+        while start < extra.len() {
+            let run = extra[start..].iter().take_while(|&&coordinate| coordinate == extra[start]).count();
+            orders /= factorial(run);
+            start += run;
+        }
+        let signs = 1 << extra.iter().filter(|&&coordinate| coordinate != 0).count();
+        orders * signs
     }
 }
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone)]
-struct HyperCube(isize, isize, isize, isize);
-
-impl NCube for HyperCube {
-    /// Returns the 3*3*3 slice centered around self
-    fn neighborhood(&self) -> Vec<HyperCube> {
-        let &HyperCube(x, y, z, w) = self;
-        let xs = [x-1, x, x+1];
-        let ys = [y-1, y, y+1];
-        let zs = [z-1, z, z+1];
-        let ws = [w-1, w, w+1];
-
-        let mut neighbors = vec![];
-        for &x in xs.iter() {
-            for &y in ys.iter() {
-                for &z in zs.iter() {
-                    for &w in ws.iter() {
-                        neighbors.push(HyperCube(x, y, z, w));
-                    }
-                }
+impl<const N: usize> LatticePoint for Point<N> {
+    /// Returns the 3^N slice centered around self, except self
+    fn adjacent(&self) -> Vec<Point<N>> {
+        // Each offset is a number of N digits in base 3, where 0, 1 and 2 stand for -1, 0 and +1
+        let offsets = 3usize.pow(N as u32);
+        let center = offsets / 2;
+        (0..offsets)
+        .filter(|&offset| offset != center)
+        .map(|mut offset| {
+            let mut coordinates = self.0;
+            for coordinate in coordinates.iter_mut() {
+                *coordinate += (offset % 3) as isize - 1;
+                offset /= 3;
             }
-        }
-
-        neighbors
-
+            Point(coordinates)
+        })
+        .collect()
     }
 }
 
-#[derive(Clone)]
-struct Grid<T>(HashSet<T>);
+/// How many dimensions `simulate` supports
+pub const DIMENSIONS: RangeInclusive<usize> = 3..=6;
+
+/// The boot process
+pub const CYCLES: usize = 6;
 
-struct Sequence<T>(Grid<T>);
+/// An inactive cube becomes active with exactly 3 active neighbors, and an active one stays active with 2 or 3
+pub const RULE: Rule = Rule::new(&[3], &[2, 3]);
 
-impl<T: NCube + Hash + Eq + Clone> Iterator for Sequence<T> {
-    type Item = Grid<T>;
+/// Returns the number of active cubes after the given number of cycles
+pub fn run<T: LatticePoint>(active: Vec<T>, cycles: usize) -> usize {
+    let mut pocket = Sparse::new(Lattice::new(), RULE, active);
+    pocket.run(cycles);
+    pocket.population()
+}
 
-    fn next(&mut self) -> Option<Grid<T>> {
-        let set = self.0.0
+/// Same as `run` on the lifted slice, but only simulating canonical cubes, which makes 5 and 6 dimensions bearable
+pub fn run_symmetric<const N: usize>(slice: &Grid<bool>, cycles: usize) -> usize {
+    let mut active: HashSet<Point<N>> = lift(slice).into_iter().collect();
+    for _ in 0..cycles {
+        let candidates: HashSet<Point<N>> = active
         .iter()
-        .map(|cube| {
-            cube.neighborhood()
-        })
-        .flatten()
-        .fold(HashMap::new(), |mut count, cube| {
-            let &amount = count.get(&cube).unwrap_or(&0usize);
-            count.insert(cube, amount + 1);
-            count
-        })
+        .flat_map(|cube| cube.adjacent())
+        .map(|cube| cube.canonical())
+        .chain(active.iter().cloned())
+        .collect();
+        active = candidates
         .into_iter()
-        .fold(HashSet::new(), |mut grid, (cube, neighbors)| {
-            if self.0.0.contains(&cube) {
-                // Remember that neighbors counts the center cube itself!
-                if neighbors == 3 || neighbors == 4 {
-                    grid.insert(cube);
-                }
-            } else {
-                if neighbors == 3 {
-                    grid.insert(cube);
-                }
-            }
-            grid
-        });
-
-        let grid = Grid(set);
-        self.0 = grid.clone();
-        Some(grid)
-
+        .filter(|cube| {
+            let neighbors = cube.adjacent().iter().filter(|neighbor| active.contains(&neighbor.canonical())).count();
+            RULE.next(active.contains(cube), neighbors)
+        })
+        .collect();
     }
+    active.iter().map(Point::multiplicity).sum()
 }
 
-fn run<T: NCube + Hash + Eq + Clone>(grid: Grid<T>, iterations: usize) -> Grid<T> {
-    Sequence(grid).nth(iterations - 1).unwrap()
+pub fn parse(lines: &[String]) -> Result<Grid<bool>> {
+    Grid::parse(lines, |c| match c {
+        '.' => Some(false),
+        '#' => Some(true),
+        _ => None,
+    })
 }
 
-fn text_to_grid_3(text: Vec<String>) -> Grid<Cube> {
-
-    let mut grid: HashSet<Cube> = HashSet::new();
+/// The active cubes of the initial slice, in N dimensions
+pub fn lift<const N: usize>(slice: &Grid<bool>) -> Vec<Point<N>> {
+    slice.iter().filter(|&(_, &active)| active).map(|((x, y), _)| Point::on_slice(x as isize, y as isize)).collect()
+}
 
-    for (y, line) in text.into_iter().enumerate() {
-        for (x, content) in line.chars().enumerate() {
-            if content == '#' {
-                grid.insert(Cube(x as isize, y as isize, 0));
-            }
-        }
+/// Returns the number of active cubes after the given number of cycles in a pocket dimension with that many dimensions,
+/// or None if it isn't one of `DIMENSIONS`. Uses `run_symmetric` if `symmetric`, and `run` otherwise.
+pub fn simulate(slice: &Grid<bool>, dimensions: usize, cycles: usize, symmetric: bool) -> Option<usize> {
+    match (dimensions, symmetric) {
+        (3, false) => Some(run(lift::<3>(slice), cycles)),
+        (4, false) => Some(run(lift::<4>(slice), cycles)),
+        (5, false) => Some(run(lift::<5>(slice), cycles)),
+        (6, false) => Some(run(lift::<6>(slice), cycles)),
+        (3, true) => Some(run_symmetric::<3>(slice, cycles)),
+        (4, true) => Some(run_symmetric::<4>(slice, cycles)),
+        (5, true) => Some(run_symmetric::<5>(slice, cycles)),
+        (6, true) => Some(run_symmetric::<6>(slice, cycles)),
+        _ => None,
     }
-
-    Grid(grid)
-
 }
 
-fn text_to_grid_4(text: Vec<String>) -> Grid<HyperCube> {
-
-    let mut grid: HashSet<HyperCube> = HashSet::new();
+/// Names of the coordinates past x and y
+const NAMES: [&str; 4] = ["z", "w", "v", "u"];
 
-    for (y, line) in text.into_iter().enumerate() {
-        for (x, content) in line.chars().enumerate() {
-            if content == '#' {
-                grid.insert(HyperCube(x as isize, y as isize, 0, 0));
+/// Every layer with active cubes in the format of the puzzle statement, like `z=0, w=1` followed by its rows,
+/// all of them covering the same x and y, then a summary with the bounding box and the number of active cubes
+pub fn render<const N: usize>(active: &HashSet<Point<N>>) -> String {
+    if active.is_empty() {
+        return "no active cubes\n".to_string();
+    }
+    let min: Vec<isize> = (0..N).map(|i| active.iter().map(|cube| cube.0[i]).min().unwrap()).collect();
+    let max: Vec<isize> = (0..N).map(|i| active.iter().map(|cube| cube.0[i]).max().unwrap()).collect();
+
+    // The statement goes through z first, then w...
+    let mut layers: Vec<&[isize]> = active.iter().map(|cube| &cube.0[2..]).collect::<HashSet<_>>().into_iter().collect();
+    layers.sort_by_key(|layer| layer.iter().rev().cloned().collect::<Vec<_>>());
+
+    let mut text = String::new();
+    for layer in layers {
+        let label: Vec<String> = layer.iter().zip(NAMES.iter()).map(|(coordinate, name)| format!("{}={}", name, coordinate)).collect();
+        text.push_str(&label.join(", "));
+        text.push('\n');
+        for y in min[1]..=max[1] {
+            for x in min[0]..=max[0] {
+                let mut coordinates = [0; N];
+                coordinates[0] = x;
+                coordinates[1] = y;
+                coordinates[2..].copy_from_slice(layer);
+                text.push(if active.contains(&Point(coordinates)) { '#' } else { '.' });
             }
+            text.push('\n');
         }
+        text.push('\n');
     }
 
-    Grid(grid)
-
+    let ranges: Vec<String> = ["x", "y"]
+    .iter()
+    .chain(NAMES.iter())
+    .zip(min.iter().zip(max.iter()))
+    .map(|(name, (min, max))| format!("{}={}..{}", name, min, max))
+    .collect();
+    text.push_str(&format!("{} active cubes in {}\n", active.len(), ranges.join(", ")));
+    text
 }
 
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    let grid = text_to_grid_3(lib::read_lines(file)?.collect());
-    Ok(run(grid, 6).0.len())
+/// The state before any cycles and after each of the given number of cycles, like in the statement
+pub fn walkthrough<const N: usize>(slice: &Grid<bool>, cycles: usize) -> String {
+    let mut pocket = Sparse::new(Lattice::new(), RULE, lift::<N>(slice));
+    let mut text = String::new();
+    for cycle in 0..=cycles {
+        if cycle > 0 {
+            pocket.step();
+            text.push_str("\n\n");
+        }
+        text.push_str(&match cycle {
+            0 => "Before any cycles:".to_string(),
+            1 => "After 1 cycle:".to_string(),
+            cycle => format!("After {} cycles:", cycle),
+        });
+        text.push_str("\n\n");
+        text.push_str(&render(pocket.alive()));
+    }
+    text
 }
 
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    let grid = text_to_grid_4(lib::read_lines(file)?.collect());
-    Ok(run(grid, 6).0.len())
+/// `walkthrough` in that many dimensions, or None if it isn't one of `DIMENSIONS`
+pub fn render_cycles(slice: &Grid<bool>, dimensions: usize, cycles: usize) -> Option<String> {
+    match dimensions {
+        3 => Some(walkthrough::<3>(slice, cycles)),
+        4 => Some(walkthrough::<4>(slice, cycles)),
+        5 => Some(walkthrough::<5>(slice, cycles)),
+        6 => Some(walkthrough::<6>(slice, cycles)),
+        _ => None,
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    const INPUT_1: &str =
+const INPUT_1: &str =
 ".#.
 ..#
 ###";
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
     #[test]
     fn run1() {
-        let grid = text_to_grid_3(INPUT_1.lines().map(|s| s.to_string()).collect());
-        assert_eq!(112, run(grid, 6).0.len());
+        let lines: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        assert_eq!(112, run(lift::<3>(&parse(&lines).unwrap()), 6));
     }
 
     #[test]
     fn run2() {
-        let grid = text_to_grid_4(INPUT_1.lines().map(|s| s.to_string()).collect());
-        assert_eq!(848, run(grid, 6).0.len());
+        let lines: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        assert_eq!(848, run(lift::<4>(&parse(&lines).unwrap()), 6));
+    }
+
+    #[test]
+    fn dimensions() {
+        assert_eq!(26, Point([0; 3]).adjacent().len());
+        assert_eq!(242, Point([0; 5]).adjacent().len());
+        assert!(Point([1, 2, 3]).adjacent().contains(&Point([0, 3, 3])));
+        let lines: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        let slice = parse(&lines).unwrap();
+        assert_eq!(Some(112), simulate(&slice, 3, CYCLES, false));
+        assert_eq!(Some(848), simulate(&slice, 4, CYCLES, false));
+        assert_eq!(None, simulate(&slice, 7, CYCLES, false));
+    }
+
+    #[test]
+    fn symmetric() {
+        assert_eq!(Point([1, 2, 0, 3, 3]), Point([1, 2, -3, 0, 3]).canonical());
+        // (0, 3, 3), (0, -3, 3)... in any order: 3 orders times 4 signs
+        assert_eq!(12, Point([1, 2, 0, 3, 3]).multiplicity());
+        assert_eq!(1, Point([1, 2, 0, 0]).multiplicity());
+
+        let lines: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        let slice = parse(&lines).unwrap();
+        for cycles in 0..=CYCLES {
+            assert_eq!(run(lift::<3>(&slice), cycles), run_symmetric::<3>(&slice, cycles));
+            assert_eq!(run(lift::<4>(&slice), cycles), run_symmetric::<4>(&slice, cycles));
+        }
+        assert_eq!(run(lift::<5>(&slice), 3), run_symmetric::<5>(&slice, 3));
+    }
+
+    #[test]
+    fn walkthrough_3() {
+        let lines: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        let expected = "Before any cycles:
+
+z=0
+.#.
+..#
+###
+
+5 active cubes in x=0..2, y=0..2, z=0..0
+
+
+After 1 cycle:
+
+z=-1
+#..
+..#
+.#.
+
+z=0
+#.#
+.##
+.#.
+
+z=1
+#..
+..#
+.#.
+
+11 active cubes in x=0..2, y=1..3, z=-1..1
+";
+        assert_eq!(expected, walkthrough::<3>(&parse(&lines).unwrap(), 1));
+    }
+
+    #[test]
+    fn walkthrough_4() {
+        let lines: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        let text = render_cycles(&parse(&lines).unwrap(), 4, 1).unwrap();
+        let labels: Vec<&str> = text.lines().filter(|line| line.starts_with("z=")).collect();
+        assert_eq!(vec!["z=0, w=0", "z=-1, w=-1", "z=0, w=-1", "z=1, w=-1", "z=-1, w=0"], labels[..5].to_vec());
+        assert!(text.ends_with("29 active cubes in x=0..2, y=1..3, z=-1..1, w=-1..1\n"));
     }
 
 }
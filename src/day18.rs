@@ -1,224 +1,655 @@
-use crate::lib;
-
-pub const INPUT_FILE: &str = "input/18.txt";
-
-#[derive(PartialEq, Debug, Clone, Copy)]
-enum Token {
-    Number(usize),
-    Sum,
-    Product,
-    ParenOpen(usize),
-    ParenClose(usize),
-}
-
-enum Expression {
-    Number(usize),
-    Sum(Box<Expression>, Box<Expression>),
-    Product(Box<Expression>, Box<Expression>),
-}
-
-// 1 + 2 * 3 -> (1 + 2) * 3 -> Product(Sum(1, 2), 3)
-fn tokenize(line: String) -> Vec<Token> {
-
-    let mut level = 0;
-
-    line
-    .replace('(', "]")
-    .replace(')', "[")
-    .replace(' ', "")
-    .chars()
-    .rev()
-    .map(|c| match c {
-        '0'..='9' => Token::Number(c.to_string().parse().unwrap()),
-        '*' => Token::Product,
-        '+' => Token::Sum,
-        '[' => {
-            level += 1;
-            Token::ParenOpen(level - 1)
-        },
-        ']' => {
-            level -= 1;
-            Token::ParenClose(level)
-        },
-        _ => panic!()
-    })
-    .collect()
+pub mod bytecode;
+pub mod repl;
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Range;
+
+use crate::error::{Error, Result};
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day18;
+
+impl Solver for Day18 {
+    type Input = Parsed;
+    const DAY: usize = 18;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT, answers: [Some("26457"), Some("694173")] },
+    ];
+
+    /// Every line must be an expression or a `let`, whatever the precedence rules
+    fn parse(&self, lines: Vec<String>) -> Result<Parsed> {
+        Parsed::new(lines)
+    }
+
+    fn part1(&self, parsed: &Parsed) -> Result<Answer> {
+        Ok(sum(&parsed.lines, &parsed.statements[0])?.into())
+    }
 
+    fn part2(&self, parsed: &Parsed) -> Result<Answer> {
+        Ok(sum(&parsed.lines, &parsed.statements[1])?.into())
+    }
 }
 
-#[derive(Debug, Clone)]
-enum GroupedToken {
-    Number(usize),
-    Sum,
-    Product,
-    Group(Vec<GroupedToken>),
+/// The lines of an input, along with their statements parsed with the rules of each part
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parsed {
+    pub lines: Vec<String>,
+    /// One list per entry of `PRECEDENCES`
+    pub statements: [Vec<Statement>; 2],
 }
 
-fn group(tokens: Vec<Token>) -> Vec<GroupedToken> {
+impl Parsed {
+    pub fn new(lines: Vec<String>) -> Result<Parsed> {
+        let statements = [parse_lines(&lines, &PRECEDENCES[0])?, parse_lines(&lines, &PRECEDENCES[1])?];
+        Ok(Parsed { lines, statements })
+    }
+}
 
-    let mut result = vec![];
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    /// Has the sign of the left operand, like Rust's `%`
+    Remainder,
+    Power,
+}
 
-    let mut iter = tokens.into_iter();
-    while let Some(token) = iter.next() {
-        result.push(match token {
-            Token::Number(value) => GroupedToken::Number(value),
-            Token::Sum => GroupedToken::Sum,
-            Token::Product => GroupedToken::Product,
-            Token::ParenOpen(level) => {
-                let inner_tokens = iter.by_ref().take_while(|token| *token != Token::ParenClose(level)).collect();
-                GroupedToken::Group(group(inner_tokens))
-            },
-            Token::ParenClose(_) => panic!(),
-        });
+impl Operator {
+    pub fn symbol(self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Subtract => '-',
+            Operator::Multiply => '*',
+            Operator::Divide => '/',
+            Operator::Remainder => '%',
+            Operator::Power => '^',
+        }
     }
 
-    result
+    fn from_symbol(symbol: char) -> Option<Operator> {
+        [Operator::Add, Operator::Subtract, Operator::Multiply, Operator::Divide, Operator::Remainder, Operator::Power]
+        .iter()
+        .find(|operator| operator.symbol() == symbol)
+        .cloned()
+    }
 
+    /// The value of `lhs <operator> rhs`, unless it isn't an `i64`
+    pub fn apply(self, lhs: i64, rhs: i64) -> std::result::Result<i64, Fault> {
+        match self {
+            Operator::Add => lhs.checked_add(rhs),
+            Operator::Subtract => lhs.checked_sub(rhs),
+            Operator::Multiply => lhs.checked_mul(rhs),
+            Operator::Divide | Operator::Remainder if rhs == 0 => return Err(Fault::DivisionByZero),
+            Operator::Divide => lhs.checked_div(rhs),
+            Operator::Remainder => lhs.checked_rem(rhs),
+            Operator::Power if rhs < 0 => return Err(Fault::NegativeExponent),
+            Operator::Power => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_pow(rhs)),
+        }
+        .ok_or(Fault::Overflow)
+    }
 }
 
-// 1 + (2 + 3) + 4
-fn parse(mut grouped_tokens: Vec<GroupedToken>) -> Expression {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`
+    Right,
+}
 
-    grouped_tokens.reverse();
+/// How tightly an operator binds: the higher the level, the earlier it applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub operator: Operator,
+    pub level: u8,
+    pub associativity: Associativity,
+}
 
-    let mut iter = grouped_tokens.into_iter();
-    let mut lhs = match iter.next().unwrap() {
-        GroupedToken::Number(value) => Expression::Number(value),
-        GroupedToken::Group(inner_tokens) => parse(inner_tokens),
-        _ => panic!(),
-    };
+/// The rules of every binary operator an expression may use, and how much a unary minus takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Precedence {
+    pub rules: &'static [Rule],
+    /// A unary minus applies to the operand after it along with the operators of at least this level,
+    /// e.g. `-2 ^ 2` is -4 if it is the level of `^`, and 4 if it is higher than every level
+    pub negation: u8,
+}
 
-    while let (Some(op), Some(rhs_grouped)) = (iter.next(), iter.next()) {
-        let rhs = match rhs_grouped {
-            GroupedToken::Number(value) => Expression::Number(value),
-            GroupedToken::Group(inner_tokens) => parse(inner_tokens),
-            _ => panic!(),
-        };
-        lhs = match op {
-            GroupedToken::Sum => Expression::Sum(Box::new(lhs), Box::new(rhs)),
-            GroupedToken::Product => Expression::Product(Box::new(lhs), Box::new(rhs)),
-            _ => panic!()
-        };
+impl Precedence {
+    pub fn rule(&self, operator: Operator) -> Option<Rule> {
+        self.rules.iter().find(|rule| rule.operator == operator).cloned()
     }
+}
+
+const fn left(operator: Operator, level: u8) -> Rule {
+    Rule { operator, level, associativity: Associativity::Left }
+}
 
-    lhs
+const fn right(operator: Operator, level: u8) -> Rule {
+    Rule { operator, level, associativity: Associativity::Right }
+}
 
+/// Everything is evaluated left to right
+pub const PART1: Precedence = Precedence {
+    rules: &[
+        left(Operator::Add, 1),
+        left(Operator::Subtract, 1),
+        left(Operator::Multiply, 1),
+        left(Operator::Divide, 1),
+        left(Operator::Remainder, 1),
+        left(Operator::Power, 1),
+    ],
+    negation: u8::MAX,
+};
+
+/// Additions and subtractions before multiplications and divisions, powers first
+pub const PART2: Precedence = Precedence {
+    rules: &[
+        left(Operator::Add, 2),
+        left(Operator::Subtract, 2),
+        left(Operator::Multiply, 1),
+        left(Operator::Divide, 1),
+        left(Operator::Remainder, 1),
+        right(Operator::Power, 3),
+    ],
+    negation: u8::MAX,
+};
+
+/// The rules of part 1 and part 2, in that order
+pub const PRECEDENCES: [Precedence; 2] = [PART1, PART2];
+
+/// The usual rules: powers first, then multiplications and divisions, then additions and subtractions
+pub const STANDARD: Precedence = Precedence {
+    rules: &[
+        left(Operator::Add, 1),
+        left(Operator::Subtract, 1),
+        left(Operator::Multiply, 2),
+        left(Operator::Divide, 2),
+        left(Operator::Remainder, 2),
+        right(Operator::Power, 3),
+    ],
+    negation: 3,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Number(i64),
+    Name(String),
+    Operator(Operator),
+    Open,
+    Close,
+    Let,
+    Equals,
 }
 
-fn group_sums(mut grouped_tokens: Vec<GroupedToken>) -> Vec<GroupedToken> {
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_lowercase() || c == '_'
+}
 
-    grouped_tokens.reverse();
+/// The characters of a line that something was parsed from, counting from 0
+pub type Span = Range<usize>;
 
-    let mut iter = grouped_tokens.into_iter();
-    let first = iter.next().unwrap();
-    let mut stack = vec![match first {
-        GroupedToken::Group(inner_tokens) => GroupedToken::Group(group_sums(inner_tokens)),
-        _ => first,
-    }];
+/// An error pointing at the characters of `span` in the line with the given (0-based) index
+fn error_at(index: usize, line: &str, span: &Span, message: impl Into<String>) -> Error {
+    Error::parse(index, line, message).at(Some(span.start + 1)).wide(span.len())
+}
 
-    while let (Some(op), Some(mut rhs)) = (iter.next(), iter.next()) {
-        rhs = match rhs {
-            GroupedToken::Group(inner_tokens) => GroupedToken::Group(group_sums(inner_tokens)),
-            _ => rhs,
+/// The tokens of the line, each with the characters it was read from
+pub fn tokenize(index: usize, line: &str) -> Result<Vec<(Span, Token)>> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut tokens = vec![];
+    let mut column = 0;
+    while column < chars.len() {
+        let c = chars[column];
+        let start = column;
+        column += 1;
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '0'..='9' | 'a'..='z' | '_' => {
+                while column < chars.len() && (chars[column].is_ascii_digit() || is_name_start(chars[column])) {
+                    column += 1;
+                }
+                let word: String = chars[start..column].iter().collect();
+                let error = |message: String| error_at(index, line, &(start..column), message);
+                match word.as_str() {
+                    "let" => Token::Let,
+                    _ if is_name_start(c) => Token::Name(word),
+                    _ if word.chars().all(|c| c.is_ascii_digit()) => {
+                        Token::Number(word.parse().map_err(|_| error(format!("{} is too large", word)))?)
+                    },
+                    _ => return Err(error(format!("expected a number, found {:?}", word))),
+                }
+            },
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '=' => Token::Equals,
+            _ => match Operator::from_symbol(c) {
+                Some(operator) => Token::Operator(operator),
+                None => return Err(error_at(index, line, &(start..column), format!("unexpected {:?}", c))),
+            },
         };
-        match op {
-            GroupedToken::Sum => {
-                let lhs = stack.pop().unwrap();
-                stack.push(GroupedToken::Group(vec![lhs, GroupedToken::Sum, rhs]));
+        tokens.push((start..column, token));
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Variables and operators keep the characters they were read from, to point at them when they have no value
+pub enum Expression {
+    Number(i64),
+    Variable(String, Span),
+    /// With the span of the minus sign
+    Negate(Span, Box<Expression>),
+    Binary(Operator, Span, Box<Expression>, Box<Expression>),
+}
+
+/// Every operation is parenthesised, to show how the expression was parsed
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::Number(value) => write!(f, "{}", value),
+            Expression::Variable(name, _) => write!(f, "{}", name),
+            Expression::Negate(_, operand) => write!(f, "(-{})", operand),
+            Expression::Binary(operator, _, lhs, rhs) => write!(f, "({} {} {})", lhs, operator.symbol(), rhs),
+        }
+    }
+}
+
+/// A line of the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    /// `let <name> = <expression>`, which gives a value to a variable for the lines after it
+    Let(String, Expression),
+    /// An expression, whose value counts towards the sum
+    Expression(Expression),
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Statement::Let(name, expression) => write!(f, "let {} = {}", name, expression),
+            Statement::Expression(expression) => write!(f, "{}", expression),
+        }
+    }
+}
+
+/// A Pratt parser over the tokens of a line
+struct Parser<'a> {
+    index: usize,
+    line: &'a str,
+    tokens: Vec<(Span, Token)>,
+    /// Position of the next token
+    position: usize,
+    precedence: &'a Precedence,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    /// The characters of the next token, which must exist
+    fn span(&self) -> Span {
+        self.tokens[self.position].0.clone()
+    }
+
+    /// An error at the token at `position`, or right after the end of the line
+    fn error_at(&self, position: usize, message: impl Into<String>) -> Error {
+        let end = self.line.chars().count();
+        let span = self.tokens.get(position).map_or(end..end + 1, |(span, _)| span.clone());
+        error_at(self.index, self.line, &span, message)
+    }
+
+    /// An error at the next token
+    fn error(&self, message: impl Into<String>) -> Error {
+        self.error_at(self.position, message)
+    }
+
+    /// Skip the next token, failing unless it is `token`
+    fn expect(&mut self, token: Token, message: &str) -> Result<()> {
+        if self.peek() != Some(&token) {
+            return Err(self.error(message));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    /// A number, a variable, a parenthesised expression, or any of them after a unary minus
+    fn operand(&mut self) -> Result<Expression> {
+        let expression = match self.peek() {
+            Some(&Token::Number(value)) => Expression::Number(value),
+            Some(Token::Name(name)) => Expression::Variable(name.clone(), self.span()),
+            Some(Token::Operator(Operator::Subtract)) => {
+                let minus = self.span();
+                self.position += 1;
+                return Ok(Expression::Negate(minus, Box::new(self.expression(self.precedence.negation)?)));
+            },
+            Some(Token::Open) => {
+                let open = self.position;
+                self.position += 1;
+                let inner = self.expression(0)?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(self.error_at(open, "unmatched `(`"));
+                }
+                inner
             },
-            GroupedToken::Product => {
-                stack.push(op);
-                stack.push(rhs);
+            // An operator right after another one or at the start is missing its left operand
+            Some(&Token::Operator(operator)) => {
+                return Err(self.error(format!("`{}` is missing its left operand", operator.symbol())));
             },
-            _ => panic!()
+            None => match self.position.checked_sub(1).map(|previous| &self.tokens[previous].1) {
+                Some(Token::Operator(operator)) => {
+                    let message = format!("`{}` is missing its right operand", operator.symbol());
+                    return Err(self.error_at(self.position - 1, message));
+                },
+                _ => return Err(self.error("expected a number, a variable or `(`")),
+            },
+            _ => return Err(self.error("expected a number, a variable or `(`")),
+        };
+        self.position += 1;
+        Ok(expression)
+    }
+
+    /// The longest expression whose operators all have at least the given level
+    fn expression(&mut self, level: u8) -> Result<Expression> {
+        let mut lhs = self.operand()?;
+        while let Some(&Token::Operator(operator)) = self.peek() {
+            let rule = self.precedence.rule(operator)
+            .ok_or_else(|| self.error(format!("`{}` has no precedence", operator.symbol())))?;
+            if rule.level < level {
+                break;
+            }
+            let span = self.span();
+            self.position += 1;
+            let rhs = match rule.associativity {
+                Associativity::Left => self.expression(rule.level.saturating_add(1))?,
+                Associativity::Right => self.expression(rule.level)?,
+            };
+            lhs = Expression::Binary(operator, span, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn statement(&mut self) -> Result<Statement> {
+        if self.peek() != Some(&Token::Let) {
+            return Ok(Statement::Expression(self.expression(0)?));
+        }
+        self.position += 1;
+        let name = match self.peek() {
+            Some(Token::Name(name)) => name.clone(),
+            _ => return Err(self.error("expected a variable name")),
         };
+        self.position += 1;
+        self.expect(Token::Equals, "expected `=`")?;
+        Ok(Statement::Let(name, self.expression(0)?))
+    }
+}
+
+/// Parse the line with the given (0-based) index
+pub fn parse(index: usize, line: &str, precedence: &Precedence) -> Result<Statement> {
+    let tokens = tokenize(index, line)?;
+    let mut parser = Parser { index, line, tokens, position: 0, precedence };
+    let statement = parser.statement()?;
+    match parser.peek() {
+        None => Ok(statement),
+        Some(Token::Close) => Err(parser.error("unmatched `)`")),
+        Some(_) => Err(parser.error("expected an operator")),
+    }
+}
+
+/// Why an expression has no value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// The value doesn't fit in an `i64`
+    Overflow,
+    DivisionByZero,
+    NegativeExponent,
+    Undefined(String),
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fault::Overflow => write!(f, "overflow"),
+            Fault::DivisionByZero => write!(f, "division by zero"),
+            Fault::NegativeExponent => write!(f, "negative exponent"),
+            Fault::Undefined(name) => write!(f, "undefined variable `{}`", name),
+        }
+    }
+}
+
+/// An error for a line without a value, pointing at the characters at fault if there are any.
+/// The sum of the lines overflowing isn't the fault of any of them in particular.
+pub fn fault_at(index: usize, line: &str, span: Option<&Span>, fault: &Fault) -> Error {
+    match span {
+        Some(span) => error_at(index, line, span, fault.to_string()),
+        None => Error::parse(index, line, fault.to_string()),
     }
+}
+
+/// Values of the variables defined so far
+pub type Variables = HashMap<String, i64>;
 
-    stack
+/// The value of an expression, or why it has none along with the variable or operator at fault
+pub fn evaluate(expression: &Expression, variables: &Variables) -> std::result::Result<i64, (Span, Fault)> {
+    match expression {
+        Expression::Number(value) => Ok(*value),
+        Expression::Variable(name, span) => variables.get(name).cloned().ok_or_else(|| (span.clone(), Fault::Undefined(name.clone()))),
+        Expression::Negate(span, operand) => {
+            evaluate(operand, variables)?.checked_neg().ok_or_else(|| (span.clone(), Fault::Overflow))
+        },
+        Expression::Binary(operator, span, lhs, rhs) => {
+            operator.apply(evaluate(lhs, variables)?, evaluate(rhs, variables)?).map_err(|fault| (span.clone(), fault))
+        },
+    }
+}
 
+/// Run a statement, returning the value of the expression if it isn't a `let`
+pub fn execute(statement: &Statement, variables: &mut Variables) -> std::result::Result<Option<i64>, (Span, Fault)> {
+    match statement {
+        Statement::Let(name, expression) => {
+            let value = evaluate(expression, variables)?;
+            variables.insert(name.clone(), value);
+            Ok(None)
+        },
+        Statement::Expression(expression) => evaluate(expression, variables).map(Some),
+    }
 }
 
-fn evaluate(expr: Expression) -> usize {
-    match expr {
-        Expression::Number(value) => value,
-        Expression::Sum(lhs, rhs) => evaluate(*lhs) + evaluate(*rhs),
-        Expression::Product(lhs, rhs) => evaluate(*lhs) * evaluate(*rhs),
+/// Run the statement of the line with the given (0-based) index and add its value to `sum`, if it has one
+fn run_statement(index: usize, line: &str, statement: &Statement, variables: &mut Variables, sum: &mut i64) -> Result<()> {
+    let located = |(span, fault): (Span, Fault)| fault_at(index, line, Some(&span), &fault);
+    if let Some(value) = execute(statement, variables).map_err(located)? {
+        *sum = sum.checked_add(value).ok_or_else(|| fault_at(index, line, None, &Fault::Overflow))?;
     }
+    Ok(())
+}
+
+/// Parse the line with the given (0-based) index, then run it like `run_statement`
+fn run_line(index: usize, line: &str, precedence: &Precedence, variables: &mut Variables, sum: &mut i64) -> Result<()> {
+    run_statement(index, line, &parse(index, line, precedence)?, variables, sum)
 }
 
-fn run<T: Iterator<Item=String>>(lines: T, first: bool) -> usize {
+/// Parse every line, failing at the first one that isn't valid
+pub fn parse_lines(lines: &[String], precedence: &Precedence) -> Result<Vec<Statement>> {
     lines
-    .map(tokenize)
-    .map(group)
-    .map(|grouped_tokens| {
-        if first {
-            grouped_tokens
-        } else {
-            group_sums(grouped_tokens)
-        }
-    })
-    .map(parse)
-    .map(evaluate)
-    .sum()
+    .iter()
+    .enumerate()
+    .map(|(index, line)| parse(index, line, precedence))
+    .collect()
+}
+
+/// Sum of the values of the statements parsed from `lines`, one per line
+pub fn sum(lines: &[String], statements: &[Statement]) -> Result<i64> {
+    let mut variables = Variables::new();
+    let mut sum = 0;
+    for (index, (line, statement)) in lines.iter().zip(statements).enumerate() {
+        run_statement(index, line, statement, &mut variables, &mut sum)?;
+    }
+    Ok(sum)
 }
 
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    Ok(run(lib::read_lines(file)?, true))
+/// Sum of the values of the lines, parsing each one right before running it
+pub fn run(lines: &[String], precedence: &Precedence) -> Result<i64> {
+    let mut variables = Variables::new();
+    let mut sum = 0;
+    for (index, line) in lines.iter().enumerate() {
+        run_line(index, line, precedence, &mut variables, &mut sum)?;
+    }
+    Ok(sum)
 }
 
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    Ok(run(lib::read_lines(file)?, false))
+/// Same as `run`, except that the lines that can't be parsed or evaluated are left out,
+/// and returned with what's wrong with them. A `let` left out leaves its variable undefined.
+pub fn run_lenient(lines: &[String], precedence: &Precedence) -> (i64, Vec<Error>) {
+    let mut variables = Variables::new();
+    let mut sum = 0;
+    let errors = lines
+    .iter()
+    .enumerate()
+    .filter_map(|(index, line)| run_line(index, line, precedence, &mut variables, &mut sum).err())
+    .collect();
+    (sum, errors)
 }
 
+const INPUT: &str =
+"1 + 2 * 3 + 4 * 5 + 6
+1 + (2 * 3) + (4 * (5 + 6))
+2 * 3 + (4 * 5)
+5 + (8 * 3 + 9 + 3 * 4 * 3)
+5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))
+((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2";
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|s| s.to_string()).collect()
+    }
+
     #[test]
     fn test_tokenize() {
         use Token::*;
-        let input = "1 + (2 * (3 + 4))".to_string();
-        // Parser changes () to ][ and reverses order
-        let output: Vec<Token> = vec![
-            Number(1),
-            Sum,
-            ParenClose(0),
-                Number(2),
-                Product,
-                ParenClose(1),
-                    Number(3),
-                    Sum,
-                    Number(4),
-                ParenOpen(1),
-            ParenOpen(0)
-        ].into_iter().rev().collect();
-        assert_eq!(output, tokenize(input));
+        let output = vec![
+            (0..1, Number(1)),
+            (2..3, Operator(super::Operator::Add)),
+            (4..5, Open),
+            (5..7, Number(23)),
+            (8..9, Operator(super::Operator::Multiply)),
+            (10..11, Open),
+            (11..12, Name("x".to_string())),
+            (13..14, Operator(super::Operator::Add)),
+            (15..16, Number(4)),
+            (16..17, Close),
+            (17..18, Close),
+        ];
+        assert_eq!(output, tokenize(0, "1 + (23 * (x + 4))").unwrap());
     }
 
     #[test]
     fn simple_test() {
-        assert_eq!(13, run(vec!["1 + 2 * 3 + 4"].iter().map(|s| s.to_string()), true));
+        assert_eq!(13, run(&lines("1 + 2 * 3 + 4"), &PART1).unwrap());
+        assert_eq!(21, run(&lines("1 + 2 * 3 + 4"), &PART2).unwrap());
+        assert_eq!(11, run(&lines("1 + 2 * 3 + 4"), &STANDARD).unwrap());
     }
 
-    const INPUT: &str =
-"1 + 2 * 3 + 4 * 5 + 6
-1 + (2 * 3) + (4 * (5 + 6))
-2 * 3 + (4 * 5)
-5 + (8 * 3 + 9 + 3 * 4 * 3)
-5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))
-((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2";
-
     #[test]
     fn run1() {
-        let input = INPUT.lines().map(|s| s.to_string());
-        assert_eq!(71 + 51 + 26 + 437 + 12240 + 13632, run(input, true));
+        assert_eq!(71 + 51 + 26 + 437 + 12240 + 13632, run(&lines(INPUT), &PART1).unwrap());
     }
 
     // 2 * 3 + (4 * 5) -> 46
     #[test]
     fn run2() {
-        let input = INPUT.lines().map(|s| s.to_string());
-        assert_eq!(231 + 51 + 46 + 1445 + 669060 + 23340, run(input, false));
+        assert_eq!(231 + 51 + 46 + 1445 + 669060 + 23340, run(&lines(INPUT), &PART2).unwrap());
+    }
+
+    #[test]
+    fn associativity() {
+        use Expression::{Binary, Number};
+        const RIGHT: Precedence = Precedence { rules: &[right(Operator::Add, 1)], negation: 1 };
+        let sum = |span, lhs, rhs| Binary(Operator::Add, span, Box::new(lhs), Box::new(rhs));
+        let left_first = sum(6..7, sum(2..3, Number(1), Number(2)), Number(3));
+        let right_first = sum(2..3, Number(1), sum(6..7, Number(2), Number(3)));
+        assert_eq!(Statement::Expression(left_first), parse(0, "1 + 2 + 3", &PART1).unwrap());
+        assert_eq!(Statement::Expression(right_first), parse(0, "1 + 2 + 3", &RIGHT).unwrap());
+        assert!(parse(0, "1 * 2", &RIGHT).is_err());
+        assert_eq!("let x = ((-(2 ^ 2)) + (3 * 4))", parse(0, "let x = -2 ^ 2 + 3 * 4", &STANDARD).unwrap().to_string());
+    }
+
+    #[test]
+    fn language() {
+        let value = |line: &str| run(&lines(line), &STANDARD).unwrap();
+        assert_eq!(1054, value("12 * 87 + 10"));
+        assert_eq!((3, 3, -1), (value("10 - 4 - 3"), value("7 / 2"), value("-7 % 3")));
+        assert_eq!((512, -4, -6), (value("2 ^ 3 ^ 2"), value("-2 ^ 2"), value("2 * -3")));
+        assert_eq!(4, run(&lines("-2 ^ 2"), &PART1).unwrap());
+        assert_eq!(40 + 43, value("let answer = 6 * 7\nanswer - 2\nlet answer = answer + 1\nanswer"));
+        assert_eq!(15, value("1\t+ 2 *\t7\r"));
+        for (text, at, fault) in [
+            ("9223372036854775807 + 1", Some(21), Fault::Overflow),
+            ("-(-9223372036854775807 - 1)", Some(1), Fault::Overflow),
+            ("2 ^ 64", Some(3), Fault::Overflow),
+            ("1 / (2 - 2)", Some(3), Fault::DivisionByZero),
+            ("2 ^ -1", Some(3), Fault::NegativeExponent),
+            ("x + 1", Some(1), Fault::Undefined("x".to_string())),
+            ("9223372036854775807\n1", None, Fault::Overflow),
+        ].iter() {
+            match run(&lines(text), &STANDARD) {
+                Err(Error::Parse { message, column, .. }) => assert_eq!((*at, fault.to_string()), (column, message), "{}", text),
+                _ => panic!("expected {} for {:?}", fault, text),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        let cases = [
+            ("1 + ", 3, 1, "`+` is missing its right operand"),
+            ("(1 + 2", 1, 1, "unmatched `(`"),
+            ("1 + 2)", 6, 1, "unmatched `)`"),
+            ("1 2", 3, 1, "expected an operator"),
+            ("1 $ 2", 3, 1, "unexpected '$'"),
+            ("* 2", 1, 1, "`*` is missing its left operand"),
+            ("1 + * 2", 5, 1, "`*` is missing its left operand"),
+            ("2 * (", 6, 1, "expected a number, a variable or `(`"),
+            ("12x", 1, 3, "expected a number, found \"12x\""),
+            ("99999999999999999999", 1, 20, "99999999999999999999 is too large"),
+            ("let = 2", 5, 1, "expected a variable name"),
+            ("let x 2", 7, 1, "expected `=`"),
+            ("x = 2", 3, 1, "expected an operator"),
+        ];
+        for &(line, column, width, expected) in cases.iter() {
+            match parse(0, line, &PART1) {
+                Err(Error::Parse { column: Some(found), width: found_width, message, .. }) => {
+                    assert_eq!((column, width, expected), (found, found_width, message.as_str()), "{}", line);
+                },
+                _ => panic!("expected a parse error for {:?}", line),
+            }
+        }
+        let diagnostic = "<input>:1:5: unmatched `(`
+  |
+1 | 1 + (2 * 3
+  |     ^";
+        assert_eq!(diagnostic, parse(0, "1 + (2 * 3", &PART1).unwrap_err().to_string());
+    }
+
+    #[test]
+    fn lenient() {
+        let (sum, errors) = run_lenient(&lines("1 + 2\n3 +\nlet x = 1 / 0\nx * 2\n(4) * 5"), &PART1);
+        assert_eq!(23, sum);
+        let lines: Vec<usize> = errors
+        .iter()
+        .map(|error| match error {
+            Error::Parse { line, .. } => *line,
+            _ => panic!("expected a parse error"),
+        })
+        .collect();
+        assert_eq!(vec![2, 3, 4], lines);
     }
 
 }
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{fault_at, parse_lines, Day18, Expression, Fault, Operator, Parsed, Precedence, Span, Statement};
+use crate::error::Result;
+use crate::solver::{Answer, Example, Solver};
+
+/// Day 18 evaluated by compiling every line to bytecode and running it on a stack machine,
+/// instead of walking the expression trees
+pub struct Bytecode;
+
+impl Solver for Bytecode {
+    type Input = Compiled;
+    const DAY: usize = 18;
+    const EXAMPLES: &'static [Example] = Day18::EXAMPLES;
+
+    /// Compiles the lines for both parts, so that solving only runs the stack machine
+    fn parse(&self, lines: Vec<String>) -> Result<Compiled> {
+        let Parsed { lines, statements } = Parsed::new(lines)?;
+        Ok(Compiled { programs: [compile(&statements[0]), compile(&statements[1])], lines })
+    }
+
+    fn part1(&self, compiled: &Compiled) -> Result<Answer> {
+        Ok(execute(&compiled.lines, &compiled.programs[0])?.into())
+    }
+
+    fn part2(&self, compiled: &Compiled) -> Result<Answer> {
+        Ok(execute(&compiled.lines, &compiled.programs[1])?.into())
+    }
+}
+
+/// The lines of an input, along with their code for each part
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compiled {
+    pub lines: Vec<String>,
+    /// One program per entry of `PRECEDENCES`
+    pub programs: [Program; 2],
+}
+
+/// An instruction of the stack machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Push(i64),
+    /// Push the value of the variable in a slot
+    Load(usize),
+    /// Pop a value into the slot of a variable
+    Store(usize),
+    /// Replace the value on top with its opposite
+    Negate,
+    /// Pop the right operand, then the left one, and push the result
+    Binary(Operator),
+    /// Pop a value and add it to the sum of the lines
+    Sum,
+}
+
+/// Flat postfix code for a whole input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    code: Vec<Op>,
+    /// 0-based index of the line each op was compiled from
+    lines: Vec<usize>,
+    /// The variable or operator each op was compiled from, if it can fail
+    spans: Vec<Option<Span>>,
+    /// Name of the variable in each slot
+    names: Vec<String>,
+}
+
+impl Program {
+    pub fn code(&self) -> &[Op] {
+        &self.code
+    }
+
+    fn emit(&mut self, line: usize, span: Option<&Span>, op: Op) {
+        self.code.push(op);
+        self.lines.push(line);
+        self.spans.push(span.cloned());
+    }
+
+    /// The slot of a variable, given the slots of the variables seen so far
+    fn slot(&mut self, slots: &mut HashMap<String, usize>, name: &str) -> usize {
+        let names = &mut self.names;
+        *slots.entry(name.to_string()).or_insert_with(|| {
+            names.push(name.to_string());
+            names.len() - 1
+        })
+    }
+
+    fn expression(&mut self, line: usize, slots: &mut HashMap<String, usize>, expression: &Expression) {
+        match expression {
+            Expression::Number(value) => self.emit(line, None, Op::Push(*value)),
+            Expression::Variable(name, span) => {
+                let slot = self.slot(slots, name);
+                self.emit(line, Some(span), Op::Load(slot));
+            },
+            Expression::Negate(span, operand) => {
+                self.expression(line, slots, operand);
+                self.emit(line, Some(span), Op::Negate);
+            },
+            Expression::Binary(operator, span, lhs, rhs) => {
+                self.expression(line, slots, lhs);
+                self.expression(line, slots, rhs);
+                self.emit(line, Some(span), Op::Binary(*operator));
+            },
+        }
+    }
+
+    /// The sum of the values of the expressions, or the (0-based) index of the line that has none,
+    /// the variable or operator at fault if any, and why.
+    /// Gives the same results as evaluating the trees, in the same order.
+    pub fn run(&self) -> std::result::Result<i64, (usize, Option<Span>, Fault)> {
+        let mut stack: Vec<i64> = vec![];
+        let mut slots: Vec<Option<i64>> = vec![None; self.names.len()];
+        let mut sum: i64 = 0;
+        for (address, &op) in self.code.iter().enumerate() {
+            let fail = |fault: Fault| (self.lines[address], self.spans[address].clone(), fault);
+            let mut pop = || stack.pop().expect("the compiler balances the stack");
+            match op {
+                Op::Push(value) => stack.push(value),
+                Op::Load(slot) => {
+                    let value = slots[slot].ok_or_else(|| fail(Fault::Undefined(self.names[slot].clone())))?;
+                    stack.push(value);
+                },
+                Op::Store(slot) => slots[slot] = Some(pop()),
+                Op::Negate => {
+                    let value = pop().checked_neg().ok_or_else(|| fail(Fault::Overflow))?;
+                    stack.push(value);
+                },
+                Op::Binary(operator) => {
+                    let rhs = pop();
+                    let lhs = pop();
+                    stack.push(operator.apply(lhs, rhs).map_err(fail)?);
+                },
+                Op::Sum => sum = sum.checked_add(pop()).ok_or_else(|| fail(Fault::Overflow))?,
+            }
+        }
+        Ok(sum)
+    }
+}
+
+/// One op per line, with their addresses, under the (1-based) number of the line they come from
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (address, (&op, &line)) in self.code.iter().zip(self.lines.iter()).enumerate() {
+            if address == 0 || self.lines[address - 1] != line {
+                writeln!(f, "; line {}", line + 1)?;
+            }
+            write!(f, "{:>6}  ", address)?;
+            match op {
+                Op::Push(value) => writeln!(f, "push {}", value)?,
+                Op::Load(slot) => writeln!(f, "load {}", self.names[slot])?,
+                Op::Store(slot) => writeln!(f, "store {}", self.names[slot])?,
+                Op::Negate => writeln!(f, "neg")?,
+                Op::Binary(operator) => writeln!(f, "{}", match operator {
+                    Operator::Add => "add",
+                    Operator::Subtract => "sub",
+                    Operator::Multiply => "mul",
+                    Operator::Divide => "div",
+                    Operator::Remainder => "rem",
+                    Operator::Power => "pow",
+                })?,
+                Op::Sum => writeln!(f, "sum")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compile the statements of an input, one per line
+pub fn compile(statements: &[Statement]) -> Program {
+    let mut program = Program { code: vec![], lines: vec![], spans: vec![], names: vec![] };
+    let mut slots = HashMap::new();
+    for (line, statement) in statements.iter().enumerate() {
+        match statement {
+            Statement::Let(name, expression) => {
+                program.expression(line, &mut slots, expression);
+                let slot = program.slot(&mut slots, name);
+                program.emit(line, None, Op::Store(slot));
+            },
+            Statement::Expression(expression) => {
+                program.expression(line, &mut slots, expression);
+                program.emit(line, None, Op::Sum);
+            },
+        }
+    }
+    program
+}
+
+/// Parse and compile every line
+pub fn compile_lines(lines: &[String], precedence: &Precedence) -> Result<Program> {
+    Ok(compile(&parse_lines(lines, precedence)?))
+}
+
+/// Run the program compiled from `lines`, pointing at the line that has no value if any
+pub fn execute(lines: &[String], program: &Program) -> Result<i64> {
+    program
+    .run()
+    .map_err(|(index, span, fault)| fault_at(index, &lines[index], span.as_ref(), &fault))
+}
+
+/// Same as `day18::run` with bytecode, except that every line is parsed before any of them runs
+pub fn run(lines: &[String], precedence: &Precedence) -> Result<i64> {
+    execute(lines, &compile_lines(lines, precedence)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::{INPUT, PART1, PART2, STANDARD};
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn same_as_tree() {
+        let programs = [
+            INPUT,
+            "let x = 6 * 7\nx - 2\nlet x = x + 1\n-x ^ 2 % 5",
+            "1 + 2\n2 ^ 70",
+            "1\nlet y = 1 / 0",
+            "x",
+        ];
+        for text in programs.iter() {
+            for precedence in [PART1, PART2, STANDARD].iter() {
+                let tree = super::super::run(&lines(text), precedence).map_err(|error| error.to_string());
+                assert_eq!(tree, run(&lines(text), precedence).map_err(|error| error.to_string()), "{}", text);
+            }
+        }
+    }
+
+    #[test]
+    fn solver() {
+        let tree = Day18.parse(lines(INPUT)).unwrap();
+        let compiled = Bytecode.parse(lines(INPUT)).unwrap();
+        assert_eq!(Day18.part1(&tree).unwrap(), Bytecode.part1(&compiled).unwrap());
+        assert_eq!(Day18.part2(&tree).unwrap(), Bytecode.part2(&compiled).unwrap());
+        let compiled = Bytecode.parse(lines("1\n2 ^ 70")).unwrap();
+        assert!(Bytecode.part1(&compiled).unwrap_err().to_string().starts_with("<input>:2:3: overflow"));
+    }
+
+    #[test]
+    fn dump() {
+        let program = compile_lines(&lines("let x = -2\n1 + x * 3"), &PART1).unwrap();
+        let expected = "; line 1
+     0  push 2
+     1  neg
+     2  store x
+; line 2
+     3  push 1
+     4  load x
+     5  add
+     6  push 3
+     7  mul
+     8  sum
+";
+        assert_eq!(expected, program.to_string());
+        assert_eq!(Ok(-3), program.run());
+        assert_eq!(Err((1, Some(2..3), Fault::DivisionByZero)), compile_lines(&lines("1\n7 / 0"), &PART1).unwrap().run());
+    }
+
+}
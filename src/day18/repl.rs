@@ -0,0 +1,101 @@
+use super::{execute, parse, Precedence, Statement, Variables, PART1, PART2, STANDARD};
+use crate::error::Error;
+
+/// The precedence rules that `:mode` can switch to
+pub const MODES: [(&str, Precedence); 3] = [("part1", PART1), ("part2", PART2), ("standard", STANDARD)];
+
+pub const PROMPT: &str = "> ";
+
+const HELP: &str = "Type an expression to evaluate it, or `let <name> = <expression>` to define a variable.
+    :mode            show the precedence rules in use
+    :mode <mode>     switch to the rules of part1, part2 or standard
+    :help            show this message
+    :quit            leave";
+
+/// Evaluates the lines typed one after another, keeping the variables defined so far
+pub struct Repl {
+    /// Index in `MODES`
+    mode: usize,
+    variables: Variables,
+}
+
+impl Repl {
+    /// Starts with the rules of part 1
+    pub fn new() -> Repl {
+        Repl { mode: 0, variables: Variables::new() }
+    }
+
+    pub fn mode(&self) -> &'static str {
+        MODES[self.mode].0
+    }
+
+    /// What to print after the user typed `input` after the prompt, or None once they want to leave
+    pub fn input(&mut self, input: &str) -> Option<String> {
+        let input = input.trim_end();
+        let reply = match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [] => String::new(),
+            [":quit"] => return None,
+            [":help"] => HELP.to_string(),
+            [":mode"] => format!("mode {}", self.mode()),
+            [":mode", mode] => match MODES.iter().position(|(name, _)| name == mode) {
+                Some(index) => {
+                    self.mode = index;
+                    format!("mode {}", self.mode())
+                },
+                None => format!("unknown mode {:?}, expected part1, part2 or standard", mode),
+            },
+            [command, ..] if command.starts_with(':') => format!("unknown command {:?}, try :help", command),
+            _ => self.evaluate(input),
+        };
+        Some(reply)
+    }
+
+    /// The parsed statement with its value, or what's wrong with it
+    fn evaluate(&mut self, input: &str) -> String {
+        let statement = match parse(0, input, &MODES[self.mode].1) {
+            Ok(statement) => statement,
+            // Point under the input, right after the prompt
+            Err(Error::Parse { column: Some(column), width, message, .. }) => {
+                return format!("{}{} {}", " ".repeat(PROMPT.len() + column - 1), "^".repeat(width), message);
+            },
+            Err(error) => return error.to_string(),
+        };
+        match (execute(&statement, &mut self.variables), &statement) {
+            (Ok(Some(value)), _) => format!("{} = {}", statement, value),
+            (Ok(None), Statement::Let(name, _)) => format!("{}\n{} = {}", statement, name, self.variables[name]),
+            (Ok(None), _) => statement.to_string(),
+            (Err((_, fault)), _) => format!("{}: {}", statement, fault),
+        }
+    }
+}
+
+impl Default for Repl {
+    fn default() -> Repl {
+        Repl::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn session() {
+        let mut repl = Repl::new();
+        let mut input = |line: &str| repl.input(line).unwrap();
+        assert_eq!("((1 + 2) * 3) = 9", input("1 + 2 * 3"));
+        assert_eq!("mode part2", input(":mode part2"));
+        assert_eq!("((2 * (3 + 4)) * 5) = 70", input("2 * 3 + 4 * 5"));
+        assert_eq!("let x = (6 * 7)\nx = 42", input("let x = 6 * 7"));
+        assert_eq!("mode standard", input(":mode standard"));
+        assert_eq!("(x - (1 * 2)) = 40", input("x - 1 * 2"));
+        assert_eq!("(x / 0): division by zero", input("x / 0"));
+        assert_eq!("      ^ `*` is missing its left operand", input("1 + * 2"));
+        assert_eq!("      ^^^ expected a number, found \"12x\"", input("1 + 12x"));
+        assert!(input(":mode part3").starts_with("unknown mode"));
+        assert!(input(":what").starts_with("unknown command"));
+        assert_eq!("", input("  "));
+        assert_eq!(None, repl.input(":quit"));
+    }
+
+}
@@ -2,59 +2,108 @@ use std::collections::HashMap;
 
 use regex::Regex;
 
-use crate::lib;
+use crate::error::{Error, Result, parse_token};
+use crate::read_sections;
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day19;
+
+impl Solver for Day19 {
+    type Input = Satellite;
+    const DAY: usize = 19;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("2"), None] },
+        Example { input: INPUT_2, answers: [Some("3"), Some("12")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Satellite> {
+        parse_file(&lines)
+    }
+
+    fn part1(&self, satellite: &Satellite) -> Result<Answer> {
+        Ok(count_matches(satellite, false)?.into())
+    }
 
-pub const INPUT_FILE: &str = "input/19.txt";
+    fn part2(&self, satellite: &Satellite) -> Result<Answer> {
+        Ok(count_matches(satellite, true)?.into())
+    }
+}
 
-enum Rule {
+pub enum Rule {
     Letter(char),
     List(Vec<usize>),
     Or(Vec<usize>, Vec<usize>),
 }
 
-fn parse(line: String) -> Rule {
+/// The rules and the received messages
+pub struct Satellite {
+    pub rules: HashMap<usize, Rule>,
+    pub messages: Vec<String>,
+}
 
-    if line.starts_with('"') {
-        return Rule::Letter(line.chars().nth(1).unwrap())
+/// Parse the body of the rule, i.e. what comes after `<id>: `
+fn parse(index: usize, line: &str, body: &str) -> Result<Rule> {
+
+    let list = |list: &str| -> Result<Vec<usize>> {
+        list.split(' ').map(|s| parse_token(index, line, s, "a rule number")).collect()
+    };
+
+    if body.starts_with('"') {
+        let mut chars = body.chars();
+        return match (chars.next(), chars.next(), chars.next(), chars.next()) {
+            (Some('"'), Some(letter), Some('"'), None) => Ok(Rule::Letter(letter)),
+            _ => Err(Error::token(index, line, body, "expected a single letter between quotes")),
+        };
     }
 
-    if line.contains('|') {
-        let mut split = line.split(" | ");
-        let first = split.next().unwrap();
-        let second = split.next().unwrap();
-        return Rule::Or(
-            first.split(' ').map(|s| s.parse().unwrap()).collect(),
-            second.split(' ').map(|s| s.parse().unwrap()).collect(),
-        );
+    if let Some((first, second)) = body.split_once(" | ") {
+        return Ok(Rule::Or(list(first)?, list(second)?));
     }
 
-    Rule::List(line.split(' ').map(|s| s.parse().unwrap()).collect())
+    Ok(Rule::List(list(body)?))
 
 }
 
-fn parse_lines<T: Iterator<Item=String>>(lines: T) -> HashMap<usize, Rule> {
+fn parse_lines<'a>(lines: impl Iterator<Item=(usize, &'a str)>) -> Result<HashMap<usize, Rule>> {
     lines
-    .map(|line| {
-        let mut split = line.split(": ");
-        let id: usize = split.next().unwrap().parse().unwrap();
-        let body = split.next().unwrap().to_string();
-        (id, parse(body))
+    .map(|(index, line)| {
+        let (id, body) = line.split_once(": ").ok_or_else(|| Error::parse(index, line, "expected `<id>: <rule>`"))?;
+        Ok((parse_token(index, line, id, "a rule number")?, parse(index, line, body)?))
     })
     .collect()
 }
 
-fn list_to_regex(list: &Vec<usize>, rules: &HashMap<usize, Rule>, regexs: &mut HashMap<usize, String>, recursive: bool) -> String {
-    list.into_iter().map(|index| {
-        if let Some(regex) = regexs.get(index) {
-            regex.clone()
-        } else if recursive {
+/// The rules, then an empty line, then the messages
+pub fn parse_file(lines: &[String]) -> Result<Satellite> {
+    let sections = read_sections(lines, &["the rules", "the messages"])?;
+    Ok(Satellite {
+        rules: parse_lines(sections[0].lines())?,
+        messages: sections[1].lines().map(|(_, line)| line.to_string()).collect(),
+    })
+}
+
+/// Look up a rule, and mark it as being turned into a regex so that loops are caught
+fn get<'a>(index: usize, rules: &'a HashMap<usize, Rule>, regexs: &mut HashMap<usize, Option<String>>) -> Result<&'a Rule> {
+    regexs.insert(index, None);
+    rules.get(&index).ok_or_else(|| Error::input(format!("rule {} is not defined", index)))
+}
+
+/// Regexs already built are memoized in `regexs`; rules still being built are mapped to None
+fn list_to_regex(list: &[usize], rules: &HashMap<usize, Rule>, regexs: &mut HashMap<usize, Option<String>>, recursive: bool) -> Result<String> {
+    list.iter().map(|index| {
+        match regexs.get(index) {
+            Some(Some(regex)) => return Ok(regex.clone()),
+            Some(None) => return Err(Error::input(format!("rule {} refers to itself", index))),
+            None => {},
+        }
+        let regex = if recursive {
             // 8: 42 | 42 8
             // 11: 42 31 | 42 11 31
-            let regex = match *index {
-                8 => format!("(?:{})+", to_regex(rules.get(&42).unwrap(), rules, regexs, recursive)),
+            match *index {
+                8 => format!("(?:{})+", list_to_regex(&[42], rules, regexs, recursive)?),
                 11 => {
-                    let r42 = to_regex(rules.get(&42).unwrap(), rules, regexs, recursive);
-                    let r31 = to_regex(rules.get(&31).unwrap(), rules, regexs, recursive);
+                    let r42 = list_to_regex(&[42], rules, regexs, recursive)?;
+                    let r31 = list_to_regex(&[31], rules, regexs, recursive)?;
 
                     let mut res = format!("(?:{}{})?", r42, r31);
                     // To any and all future employers and reviewers: please have mercy upon my code
@@ -63,64 +112,45 @@ fn list_to_regex(list: &Vec<usize>, rules: &HashMap<usize, Rule>, regexs: &mut H
                     }
                     format!("{}{}{}", r42, res, r31)
                 },
-                _ => to_regex(rules.get(index).unwrap(), rules, regexs, recursive),
-            };
-            regexs.insert(*index, regex.clone());
-            regex
+                _ => to_regex(get(*index, rules, regexs)?, rules, regexs, recursive)?,
+            }
         } else {
-            let regex = to_regex(rules.get(index).unwrap(), rules, regexs, recursive);
-            regexs.insert(*index, regex.clone());
-            regex
-        }
+            to_regex(get(*index, rules, regexs)?, rules, regexs, recursive)?
+        };
+        regexs.insert(*index, Some(regex.clone()));
+        Ok(regex)
     })
-    .collect::<String>()
+    .collect()
 }
 
-fn to_regex(rule: &Rule, rules: &HashMap<usize, Rule>, regexs: &mut HashMap<usize, String>, recursive: bool) -> String {
-    match rule {
-        Rule::Letter(letter) => letter.to_string(),
-        Rule::List(list) => list_to_regex(list, rules, regexs, recursive),
+fn to_regex(rule: &Rule, rules: &HashMap<usize, Rule>, regexs: &mut HashMap<usize, Option<String>>, recursive: bool) -> Result<String> {
+    Ok(match rule {
+        Rule::Letter(letter) => regex::escape(&letter.to_string()),
+        Rule::List(list) => list_to_regex(list, rules, regexs, recursive)?,
         Rule::Or(first, second) => {
-            let first_regex = list_to_regex(first, rules, regexs, recursive);
-            let second_regex = list_to_regex(second, rules, regexs, recursive);
+            let first_regex = list_to_regex(first, rules, regexs, recursive)?;
+            let second_regex = list_to_regex(second, rules, regexs, recursive)?;
             format!("(?:{}|{})", first_regex, second_regex)
         }
-    }
+    })
 }
 
-fn first_regex(rules: HashMap<usize, Rule>, recursive: bool) -> String {
-    let first = rules.get(&0).unwrap();
-    let regex = to_regex(first, &rules, &mut HashMap::new(), recursive);
-    format!("^{}$", regex)
+pub fn first_regex(rules: &HashMap<usize, Rule>, recursive: bool) -> Result<String> {
+    let regex = list_to_regex(&[0], rules, &mut HashMap::new(), recursive)?;
+    Ok(format!("^{}$", regex))
 }
 
-fn parse_file<T: Iterator<Item=String>>(mut lines: T, first: bool) -> usize {
-    let rules = lines
-    .by_ref()
-    .take_while(|line| line.trim() != "")
-    .map(|s| s.to_string());
-
-    let regex = Regex::new(first_regex(parse_lines(rules), first).as_str()).unwrap();
+pub fn count_matches(satellite: &Satellite, recursive: bool) -> Result<usize> {
+    let regex = Regex::new(first_regex(&satellite.rules, recursive)?.as_str())
+    .map_err(|error| Error::input(format!("the rules are too complex: {}", error)))?;
 
-    lines
+    Ok(satellite.messages
+    .iter()
     .filter(|input| regex.is_match(input.as_str()))
-    .count()
-}
-
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    Ok(parse_file(lib::read_lines(file)?, false))
-}
-
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    Ok(parse_file(lib::read_lines(file)?, true))
+    .count())
 }
 
-#[cfg(test)]
-mod test {
-
-    use super::*;
-
-    const INPUT_1: &str =
+const INPUT_1: &str =
 r#"0: 4 1 5
 1: 2 3 | 3 2
 2: 4 4 | 5 5
@@ -135,13 +165,7 @@ aaabbb
 aaaabbb
 "#;
 
-    #[test]
-    fn run1() {
-        let input = INPUT_1.lines().map(|s| s.to_string());
-        assert_eq!(2, parse_file(input, false));
-    }
-
-    const INPUT_2: &str =
+const INPUT_2: &str =
 r#"42: 9 14 | 10 1
 9: 14 27 | 1 26
 10: 23 14 | 28 1
@@ -191,10 +215,21 @@ babaaabbbaaabaababbaabababaaab
 aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba
 "#;
 
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn run1() {
+        let input: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        assert_eq!(2, count_matches(&parse_file(&input).unwrap(), false).unwrap());
+    }
+
     #[test]
     fn run2() {
-        let input = INPUT_2.lines().map(|s| s.to_string());
-        assert_eq!(12, parse_file(input, true));
+        let input: Vec<String> = INPUT_2.lines().map(|s| s.to_string()).collect();
+        assert_eq!(12, count_matches(&parse_file(&input).unwrap(), true).unwrap());
     }
 
 }
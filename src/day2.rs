@@ -1,71 +1,78 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use crate::error::{Error, Result, parse_token};
+use crate::solver::{Answer, Example, Solver};
 
-const INPUT_FILE: &str = "input/2.txt";
+pub struct Day2;
 
-pub fn run1() -> Option<u32> {
+impl Solver for Day2 {
+    type Input = Vec<Entry>;
+    const DAY: usize = 2;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: EXAMPLE, answers: [Some("2"), Some("1")] },
+    ];
 
-    // Read the input file into a vector
-    if let Ok(file) = File::open(INPUT_FILE) {
-        Some(io::BufReader::new(file).lines().into_iter().filter(|maybe_line| {
-            if let Ok(line) = maybe_line {
-
-                // Rust: [is safe]
-                // Me: let there be `.unwrap()`
-                let parts1: Vec<&str> = line.split(": ").collect();
-                let parts2: Vec<&str> = parts1[0].split(" ").collect();
-                let parts3: Vec<u8> = parts2[0].split("-").map(|s| s.parse::<u8>().unwrap()).collect();
-
-                let password = parts1[1];
-                let letter = parts2[1].parse::<char>().unwrap();
-                let min = parts3[0];
-                let max = parts3[1];
-
-                let amount = password.chars().filter(|&l| l == letter).count() as u8;
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<Entry>> {
+        lines.iter().enumerate().map(|(index, line)| parse_entry(index, line)).collect()
+    }
 
-                amount >= min && amount <= max
+    fn part1(&self, entries: &Vec<Entry>) -> Result<Answer> {
+        Ok(entries.iter().filter(|entry| valid1(entry)).count().into())
+    }
 
-            } else {
-                // No damage in returning an extra false
-                false
-            }
-        }).count() as u32)
-    } else {
-        None
+    fn part2(&self, entries: &Vec<Entry>) -> Result<Answer> {
+        Ok(entries.iter().filter(|entry| valid2(entry)).count().into())
     }
+}
 
+/// A line of the database: the policy and the password it applies to
+pub struct Entry {
+    pub first: usize,
+    pub second: usize,
+    pub letter: char,
+    pub password: String,
 }
 
-pub fn run2() -> Option<u32> {
+/// e.g. `1-3 a: abcde`
+pub fn parse_entry(index: usize, line: &str) -> Result<Entry> {
 
-    // Read the input file into a vector
-    if let Ok(file) = File::open(INPUT_FILE) {
-        Some(io::BufReader::new(file).lines().into_iter().filter(|maybe_line| {
-            if let Ok(line) = maybe_line {
+    let expected = || Error::parse(index, line, "expected `<first>-<second> <letter>: <password>`");
 
-                // Rust: [is safe]
-                // Me: let there be `.unwrap()`
-                let parts1: Vec<&str> = line.split(": ").collect();
-                let parts2: Vec<&str> = parts1[0].split(" ").collect();
-                let parts3: Vec<usize> = parts2[0].split("-").map(|s| s.parse::<usize>().unwrap()).collect();
+    let (policy, password) = line.split_once(": ").ok_or_else(expected)?;
+    let (range, letter) = policy.split_once(' ').ok_or_else(expected)?;
+    let (first, second) = range.split_once('-').ok_or_else(expected)?;
 
-                let password: Vec<char> = parts1[1].chars().collect();
-                let letter = parts2[1].parse::<char>().unwrap();
-                let first_position = parts3[0];
-                let second_position = parts3[1];
+    let first: usize = parse_token(index, line, first, "a position")?;
+    let second: usize = parse_token(index, line, second, "a position")?;
+    if first == 0 || second == 0 {
+        return Err(Error::token(index, line, range, "positions start at 1"));
+    }
 
-                let first_matches = password[first_position - 1] == letter;
-                let second_matches = password[second_position - 1] == letter;
+    Ok(Entry {
+        first,
+        second,
+        letter: parse_token(index, line, letter, "a letter")?,
+        password: password.to_string(),
+    })
 
-                (first_matches && !second_matches) || (!first_matches && second_matches)
+}
 
-            } else {
-                // No damage in returning an extra false
-                false
-            }
-        }).count() as u32)
-    } else {
-        None
-    }
+/// The letter must appear between `first` and `second` times
+pub fn valid1(entry: &Entry) -> bool {
+    let amount = entry.password.chars().filter(|&l| l == entry.letter).count();
+    amount >= entry.first && amount <= entry.second
+}
 
+/// The letter must appear in exactly one of the positions `first` and `second`
+pub fn valid2(entry: &Entry) -> bool {
+    let password: Vec<char> = entry.password.chars().collect();
+
+    // Positions past the end of the password can't hold the letter
+    let first_matches = password.get(entry.first - 1) == Some(&entry.letter);
+    let second_matches = password.get(entry.second - 1) == Some(&entry.letter);
+
+    first_matches != second_matches
 }
+
+const EXAMPLE: &str =
+"1-3 a: abcde
+1-3 b: cdefg
+2-9 c: ccccccccc";
@@ -1,25 +1,65 @@
-use std::collections::HashMap;
-use std::convert::TryInto;
+use std::collections::{HashMap, HashSet};
 
-use crate::lib;
+use crate::{Grid, read_blocks};
+use crate::blocks::Block;
+use crate::error::{Error, Result, parse_token};
+use crate::solver::{Answer, Example, Solver};
 
-pub const INPUT_FILE: &str = "input/20.txt";
+pub struct Day20;
+
+impl Solver for Day20 {
+    type Input = HashMap<usize, Pixels>;
+    const DAY: usize = 20;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("20899048083289"), Some("273")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<HashMap<usize, Pixels>> {
+        parse(&lines)
+    }
+
+    fn part1(&self, images: &HashMap<usize, Pixels>) -> Result<Answer> {
+        Ok(border_tiles(tiles(images)).into_iter().product::<usize>().into())
+    }
+
+    fn part2(&self, images: &HashMap<usize, Pixels>) -> Result<Answer> {
+        let rows = arrange(images).ok_or(Error::NoSolution)?;
+        roughness(&assemble(&rows)).map(Answer::from).ok_or(Error::NoSolution)
+    }
+}
 
 // "Tiles at the edge of the image also have this border, but the outermost edges won't line up with any other tiles."
 // Let's pray this is true and look for all the tiles with exactly 2 unalignable borders, then multiply their ids.
 
-type Border = Vec<bool>;
+pub type Border = Vec<bool>;
 
+/// Top, bottom, left and right borders of a tile, from left to right and from top to bottom
 #[derive(Debug, PartialEq, Clone)]
-struct Tile(Border, Border, Border, Border);
+pub struct Tile(pub Border, pub Border, pub Border, pub Border);
+
+/// A tile or the whole image, with `true` for each `#`
+pub type Pixels = Grid<bool>;
+
+pub fn borders(pixels: &Pixels) -> Tile {
+    Tile(
+        pixels.row(0).to_vec(),
+        pixels.row(pixels.height() - 1).to_vec(),
+        pixels.column(0).cloned().collect(),
+        pixels.column(pixels.width() - 1).cloned().collect(),
+    )
+}
+
+pub fn tiles(images: &HashMap<usize, Pixels>) -> HashMap<usize, Tile> {
+    images.iter().map(|(&id, pixels)| (id, borders(pixels))).collect()
+}
 
-fn fits(this: &Border, other: &Border) -> bool {
+pub fn fits(this: &Border, other: &Border) -> bool {
     let mut this_rev = this.clone();
     this_rev.reverse();
     this == other || &this_rev == other
 }
 
-fn border_tiles(tiles: HashMap<usize, Tile>) -> Vec<usize> {
+pub fn border_tiles(tiles: HashMap<usize, Tile>) -> Vec<usize> {
     tiles
     .clone()
     .into_iter()
@@ -29,8 +69,7 @@ fn border_tiles(tiles: HashMap<usize, Tile>) -> Vec<usize> {
         .clone()
         .into_iter()
         .filter(|(other_id, _)| *other_id != id)
-        .map(|(_, other)| vec![other.0, other.1, other.2, other.3])
-        .flatten()
+        .flat_map(|(_, other)| vec![other.0, other.1, other.2, other.3])
         .collect();
 
         let tile_clone = tile.clone();
@@ -42,7 +81,7 @@ fn border_tiles(tiles: HashMap<usize, Tile>) -> Vec<usize> {
             .into_iter()
             .find(|other| {
                 fits(this, other)
-            }) != None
+            }).is_some()
         })
         .count();
 
@@ -54,52 +93,138 @@ fn border_tiles(tiles: HashMap<usize, Tile>) -> Vec<usize> {
     .collect()
 }
 
-fn parse_tile(lines: Vec<String>) -> (usize, Tile) {
-    let id: usize = lines[0][5..9].to_string().parse().unwrap();
-    let tile = Tile(
-        lines[1].chars().map(|c| match c {
-            '.' => false,
-            _ => true,
-        })
-        .collect::<Vec<_>>().try_into().unwrap(),
-        lines[10].chars().map(|c| match c {
-            '.' => false,
-            _ => true,
-        })
-        .collect::<Vec<_>>().try_into().unwrap(),
-        lines[1..11].into_iter().map(|line| match line.chars().nth(0).unwrap() {
-            '.' => false,
-            _ => true,
-        })
-        .collect::<Vec<_>>().try_into().unwrap(),
-        lines[1..11].into_iter().map(|line| match line.chars().nth(9).unwrap() {
-            '.' => false,
-            _ => true,
-        })
-        .collect::<Vec<_>>().try_into().unwrap(),
-    );
-    (id, tile)
+/// Arrange the tiles into a square, rotating and flipping each so that its borders match those of its neighbours.
+/// Returns the oriented tiles by rows, or None if they can't be arranged.
+pub fn arrange(images: &HashMap<usize, Pixels>) -> Option<Vec<Vec<Pixels>>> {
+    let side = (1..=images.len()).find(|side| side * side >= images.len())?;
+    if side * side != images.len() {
+        return None;
+    }
+
+    // Start with a corner, turned so that the borders that don't fit any other tile are at the top and the left
+    let tiles = tiles(images);
+    let corner = *border_tiles(tiles.clone()).first()?;
+    let unmatched = |border: &Border| {
+        tiles
+        .iter()
+        .filter(|(&id, _)| id != corner)
+        .all(|(_, other)| [&other.0, &other.1, &other.2, &other.3].iter().all(|other| !fits(border, other)))
+    };
+    let first = images[&corner].orientations().into_iter().find(|pixels| {
+        let Tile(top, _, left, _) = borders(pixels);
+        unmatched(&top) && unmatched(&left)
+    })?;
+
+    let mut used: HashSet<usize> = [corner].iter().cloned().collect();
+    let mut rows = vec![vec![first]];
+    for position in 1..images.len() {
+        let (row, column) = (position / side, position % side);
+        let (id, pixels) = images
+        .iter()
+        .filter(|(id, _)| !used.contains(id))
+        .flat_map(|(&id, pixels)| pixels.orientations().into_iter().map(move |pixels| (id, pixels)))
+        .find(|(_, pixels)| {
+            let tile = borders(pixels);
+            (column == 0 || tile.2 == borders(&rows[row][column - 1]).3)
+            && (row == 0 || tile.0 == borders(&rows[row - 1][column]).1)
+        })?;
+        used.insert(id);
+        if column == 0 {
+            rows.push(vec![pixels]);
+        } else {
+            rows[row].push(pixels);
+        }
+    }
+    Some(rows)
 }
 
-fn parse<T: Iterator<Item=String>>(lines: T) -> HashMap<usize, Tile> {
-    lines
-    .collect::<Vec<_>>()
-    .chunks(12)
-    .map(|chunk| chunk.into_iter().map(|s| s.clone()).collect())
-    .map(parse_tile)
-    .collect()
+/// Join the arranged tiles into a single image, dropping their borders
+pub fn assemble(rows: &[Vec<Pixels>]) -> Pixels {
+    let inner = rows[0][0].width() - 2;
+    Grid::from_fn(rows[0].len() * inner, rows.len() * inner, |(x, y)| {
+        rows[y / inner][x / inner][(x % inner + 1, y % inner + 1)]
+    })
 }
 
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    let input = lib::read_lines(file)?;
-    Ok(border_tiles(parse(input)).into_iter().product())
+const MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+];
+
+/// Count the `#` that aren't part of any sea monster, in the orientation of the image where there are sea monsters.
+/// Returns None if there are none in any orientation.
+pub fn roughness(image: &Pixels) -> Option<usize> {
+    let monster: Vec<(usize, usize)> = MONSTER
+    .iter()
+    .enumerate()
+    .flat_map(|(y, line)| line.chars().enumerate().filter(|&(_, c)| c == '#').map(move |(x, _)| (x, y)))
+    .collect();
+    let (height, width) = (MONSTER.len(), MONSTER[0].len());
+
+    image.orientations().into_iter().find_map(|image| {
+        // Monsters may overlap, so keep track of the pixels instead of counting monsters
+        let mut monsters = HashSet::new();
+        for y in 0..(image.height() + 1).saturating_sub(height) {
+            for x in 0..(image.width() + 1).saturating_sub(width) {
+                if monster.iter().all(|&(dx, dy)| image[(x + dx, y + dy)]) {
+                    monsters.extend(monster.iter().map(|&(dx, dy)| (x + dx, y + dy)));
+                }
+            }
+        }
+        if monsters.is_empty() {
+            None
+        } else {
+            Some(image.iter().filter(|&(_, &pixel)| pixel).count() - monsters.len())
+        }
+    })
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+/// Side of the square tiles
+const SIZE: usize = 10;
+
+/// `Tile <id>:` followed by the rows
+pub fn parse_tile(block: &Block) -> Result<(usize, Pixels)> {
+    let (index, header) = block.header();
+    let id = header
+    .strip_prefix("Tile ")
+    .and_then(|rest| rest.strip_suffix(':'))
+    .ok_or_else(|| Error::parse(index, header, "expected `Tile <id>:`"))?;
+    let id: usize = parse_token(index, header, id, "a tile id")?;
 
-    const INPUT_1: &str =
+    let rows: Vec<(usize, &str)> = block.body().collect();
+    if rows.len() != SIZE {
+        return Err(Error::parse(index, header, format!("expected {} rows, found {}", SIZE, rows.len())));
+    }
+    for &(index, row) in rows.iter() {
+        if let Some((column, c)) = row.chars().enumerate().find(|&(_, c)| c != '.' && c != '#') {
+            return Err(Error::parse(index, row, format!("unexpected {:?} in the tile", c)).at(Some(column + 1)));
+        }
+        if row.len() != SIZE {
+            return Err(Error::parse(index, row, format!("expected a row of width {}", SIZE)));
+        }
+    }
+
+    Ok((id, Grid::new(SIZE, SIZE, rows.iter().flat_map(|(_, row)| row.chars().map(|c| c == '#')).collect())))
+}
+
+/// Tiles separated by empty lines
+pub fn parse(lines: &[String]) -> Result<HashMap<usize, Pixels>> {
+    let mut tiles = HashMap::new();
+    for block in read_blocks(lines) {
+        let (id, tile) = parse_tile(&block)?;
+        if tiles.insert(id, tile).is_some() {
+            let (index, header) = block.header();
+            return Err(Error::parse(index, header, format!("tile {} appears twice", id)));
+        }
+    }
+    if tiles.is_empty() {
+        return Err(Error::input("there are no tiles"));
+    }
+    Ok(tiles)
+}
+
+const INPUT_1: &str =
 "Tile 2311:
 ..##.#..#.
 ##..#.....
@@ -209,10 +334,14 @@ Tile 3079:
 ..#.###...
 ";
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
     #[test]
     fn test_fits() {
-        let input = INPUT_1.lines().map(|s| s.to_string());
-        let tiles = parse(input);
+        let input: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        let tiles = tiles(&parse(&input).unwrap());
         let mut reversed = tiles.get(&3079).unwrap().2.clone();
         reversed.reverse();
         assert!(fits(
@@ -227,12 +356,18 @@ Tile 3079:
 
     #[test]
     fn run1() {
-        let input = INPUT_1.lines().map(|s| s.to_string());
-        assert_eq!(20899048083289usize, border_tiles(parse(input)).into_iter().product());
+        let input: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        assert_eq!(20899048083289usize, border_tiles(tiles(&parse(&input).unwrap())).into_iter().product());
     }
 
-}
+    #[test]
+    fn run2() {
+        let input: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        let rows = arrange(&parse(&input).unwrap()).unwrap();
+        assert_eq!(Some(273), roughness(&assemble(&rows)));
+    }
 
+}
 
 // 3079 .1
 // 2311 .3
\ No newline at end of file
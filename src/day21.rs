@@ -1,8 +1,29 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::lib;
+use crate::error::{Error, Result};
+use crate::solver::{Answer, Example, Solver};
 
-pub const INPUT_FILE: &str = "input/21.txt";
+pub struct Day21;
+
+impl Solver for Day21 {
+    type Input = Vec<Food>;
+    const DAY: usize = 21;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("5"), Some("mxmxvkd,sqjhc,fvjkl")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<Food>> {
+        parse_foods(&lines)
+    }
+
+    fn part1(&self, foods: &Vec<Food>) -> Result<Answer> {
+        non_allergic(foods).map(Answer::from).ok_or(Error::NoSolution)
+    }
+
+    fn part2(&self, foods: &Vec<Food>) -> Result<Answer> {
+        solution(candidates(foods)).map(allergic).map(Answer::from).ok_or(Error::NoSolution)
+    }
+}
 
 // Idea:
 // 1. Walk the file and build a HashMap of candidates Allergen -> Vec<Ingredient>
@@ -10,29 +31,54 @@ pub const INPUT_FILE: &str = "input/21.txt";
 // When we find an Allergen -> Ingredient association, move it to the solution map and remove both from the candidates map
 // 3. Once the candidates map is empty, we have all the associations
 
-type Allergen = String;
-type Ingredient = String;
-
-fn candidates<T: Iterator<Item=String>>(lines: T) -> HashMap<Allergen, HashSet<Ingredient>> {
-    lines
-    // from each line extract a Vec<(Allergen, Ingredient)>
-    .map(|line| {
-        let mut ingredients_allergens = line.split(" (contains ");
-        let ingredients = ingredients_allergens.next().unwrap().split(' ');
-        let mut allergens: Vec<(Allergen, Vec<Ingredient>)> = vec![];
-        if let Some(allergens_raw) = ingredients_allergens.next() {
-            for mut allergen in allergens_raw.split(' ') {
-                // Remove trailing , or )
-                allergen = &allergen[..allergen.len() - 1];
-                allergens.push((allergen.to_string(), ingredients.clone().map(|ingredient| ingredient.to_string()).collect()));
-            }
-        }
-        allergens
+pub type Allergen = String;
+pub type Ingredient = String;
+
+/// A line of the list
+pub struct Food {
+    pub ingredients: Vec<Ingredient>,
+    pub allergens: Vec<Allergen>,
+}
+
+/// e.g. `mxmxvkd kfcds sqjhc nhms (contains dairy, fish)`, the allergens being optional
+pub fn parse_food(index: usize, line: &str) -> Result<Food> {
+    let (ingredients, allergens) = match line.split_once(" (contains ") {
+        Some((ingredients, allergens)) => (ingredients, allergens
+            .strip_suffix(')')
+            .ok_or_else(|| Error::parse(index, line, "expected `)` at the end"))?
+            .split(", ")
+            .collect()),
+        None => (line, vec![]),
+    };
+    let names = |names: Vec<&str>| -> Result<Vec<String>> {
+        names
+        .into_iter()
+        .map(|name| match name {
+            "" => Err(Error::token(index, line, name, "expected a name")),
+            _ => Ok(name.to_string()),
+        })
+        .collect()
+    };
+    Ok(Food {
+        ingredients: names(ingredients.split(' ').collect())?,
+        allergens: names(allergens)?,
+    })
+}
+
+pub fn parse_foods(lines: &[String]) -> Result<Vec<Food>> {
+    lines.iter().enumerate().map(|(index, line)| parse_food(index, line)).collect()
+}
+
+pub fn candidates(foods: &[Food]) -> HashMap<Allergen, HashSet<Ingredient>> {
+    foods
+    .iter()
+    // from each food extract a Vec<(Allergen, Ingredient)>
+    .flat_map(|food| {
+        food.allergens.iter().map(move |allergen| (allergen.clone(), food.ingredients.clone()))
     })
-    .flatten()
     .fold(HashMap::new(), |mut candidates, (allergen, ingredients)| {
         if let Some(allergen_ingredients) = candidates.remove(&allergen) {
-            candidates.insert(allergen, ingredients.into_iter().collect::<HashSet<_>>().intersection(&allergen_ingredients).map(|s| s.clone()).collect());
+            candidates.insert(allergen, ingredients.into_iter().collect::<HashSet<_>>().intersection(&allergen_ingredients).cloned().collect());
         } else {
             candidates.insert(allergen, ingredients.into_iter().collect());
         }
@@ -40,19 +86,16 @@ fn candidates<T: Iterator<Item=String>>(lines: T) -> HashMap<Allergen, HashSet<I
     })
 }
 
-fn solution(mut candidates: HashMap<Allergen, HashSet<Ingredient>>) -> HashMap<Allergen, Ingredient> {
+/// None if at some point no allergen can be pinned down to a single ingredient
+pub fn solution(mut candidates: HashMap<Allergen, HashSet<Ingredient>>) -> Option<HashMap<Allergen, Ingredient>> {
     let mut solution: HashMap<Allergen, Ingredient> = HashMap::new();
 
-    while candidates.len() > 0 {
-        let mut removed_allergen = String::new();
-        let mut removed_ingredient = String::new();
-        for (allergen, ingredients) in &candidates {
-            if ingredients.len() == 1 {
-                removed_ingredient = ingredients.clone().drain().next().unwrap();
-                removed_allergen = allergen.clone();
-                solution.insert(removed_allergen.clone(), removed_ingredient.clone());
-            }
-        }
+    while !candidates.is_empty() {
+        let (removed_allergen, removed_ingredient) = candidates
+        .iter()
+        .find(|(_, ingredients)| ingredients.len() == 1)
+        .and_then(|(allergen, ingredients)| Some((allergen.clone(), ingredients.iter().next()?.clone())))?;
+        solution.insert(removed_allergen.clone(), removed_ingredient.clone());
         candidates = candidates
         .into_iter()
         .filter(|(allergen, _)| *allergen != removed_allergen)
@@ -63,26 +106,23 @@ fn solution(mut candidates: HashMap<Allergen, HashSet<Ingredient>>) -> HashMap<A
         .collect();
     }
 
-    solution
+    Some(solution)
 }
 
-fn non_allergic<T: Iterator<Item=String> + Clone>(lines: T) -> usize {
+pub fn non_allergic(foods: &[Food]) -> Option<usize> {
 
-    let allergic_ingredients: HashSet<Ingredient> = solution(candidates(lines.clone())).into_iter()
-    .map(|(_, ingredient)| ingredient)
+    let allergic_ingredients: HashSet<Ingredient> = solution(candidates(foods))?.into_values()
     .collect();
 
-    lines
-    .map(|line| {
-        line.split(" (contains ").next().unwrap().split(' ').map(|s| s.to_string()).collect::<Vec<Ingredient>>()
-    })
-    .flatten()
-    .filter(|ingredient| !allergic_ingredients.contains(ingredient))
-    .count()
+    Some(foods
+    .iter()
+    .flat_map(|food| food.ingredients.iter())
+    .filter(|ingredient| !allergic_ingredients.contains(*ingredient))
+    .count())
 
 }
 
-fn allergic(allergens_ingredients: HashMap<Allergen, Ingredient>) -> String {
+pub fn allergic(allergens_ingredients: HashMap<Allergen, Ingredient>) -> String {
     let mut allergens_ingredients_vec: Vec<(Allergen, Ingredient)> = allergens_ingredients.into_iter().collect();
 
     allergens_ingredients_vec.sort_unstable();
@@ -93,30 +133,20 @@ fn allergic(allergens_ingredients: HashMap<Allergen, Ingredient>) -> String {
     .join(",")
 }
 
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    Ok(non_allergic(lib::read_lines(file)?.collect::<Vec<_>>().into_iter()))
-}
-
-pub fn run2(file: &str) -> std::io::Result<String> {
-    let input = lib::read_lines(file)?;
-    Ok(allergic(solution(candidates(input))))
-}
-
-
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    const INPUT_1: &str =
+const INPUT_1: &str =
 "mxmxvkd kfcds sqjhc nhms (contains dairy, fish)
 trh fvjkl sbzzf mxmxvkd (contains dairy)
 sqjhc fvjkl (contains soy)
 sqjhc mxmxvkd sbzzf (contains fish)
 ";
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
     #[test]
     fn test_candidates() {
-        let input = INPUT_1.lines().map(|s| s.to_string());
+        let input = parse_foods(&INPUT_1.lines().map(|s| s.to_string()).collect::<Vec<_>>()).unwrap();
         let output: HashMap<Allergen, HashSet<Ingredient>> = vec![
             ("dairy", vec!["mxmxvkd"].into_iter().collect()),
             ("fish", vec!["mxmxvkd", "sqjhc"].into_iter().collect()),
@@ -128,12 +158,12 @@ sqjhc mxmxvkd sbzzf (contains fish)
             i.into_iter().map(|s| s.to_string()).collect(),
         ))
         .collect();
-        assert_eq!(output, candidates(input));
+        assert_eq!(output, candidates(&input));
     }
 
     #[test]
     fn test_solution() {
-        let input = INPUT_1.lines().map(|s| s.to_string());
+        let input = parse_foods(&INPUT_1.lines().map(|s| s.to_string()).collect::<Vec<_>>()).unwrap();
         let output: HashMap<Allergen, Ingredient> = vec![
             ("dairy", "mxmxvkd"),
             ("fish", "sqjhc"),
@@ -142,19 +172,19 @@ sqjhc mxmxvkd sbzzf (contains fish)
         .into_iter()
         .map(|(a, i): (&str, &str)| (a.to_string(), i.to_string()))
         .collect();
-        assert_eq!(output, solution(candidates(input)));
+        assert_eq!(output, solution(candidates(&input)).unwrap());
     }
 
     #[test]
     fn run1() {
-        let input = INPUT_1.lines().map(|s| s.to_string());
-        assert_eq!(5, non_allergic(input));
+        let input = parse_foods(&INPUT_1.lines().map(|s| s.to_string()).collect::<Vec<_>>()).unwrap();
+        assert_eq!(5, non_allergic(&input).unwrap());
     }
 
     #[test]
     fn run2() {
-        let input = INPUT_1.lines().map(|s| s.to_string());
-        assert_eq!("mxmxvkd,sqjhc,fvjkl", allergic(solution(candidates(input))));
+        let input = parse_foods(&INPUT_1.lines().map(|s| s.to_string()).collect::<Vec<_>>()).unwrap();
+        assert_eq!("mxmxvkd,sqjhc,fvjkl", allergic(solution(candidates(&input)).unwrap()));
     }
 
-}
\ No newline at end of file
+}
@@ -3,23 +3,62 @@ pub mod part2;
 
 use std::collections::VecDeque;
 
-pub const INPUT_FILE: &str = "input/22.txt";
+use crate::blocks::Block;
+use crate::error::{Result, parse_token};
+use crate::read_sections;
+use crate::solver::{Answer, Example, Solver};
 
-fn parse<T: Iterator<Item=String>>(mut lines: T) -> (VecDeque<usize>, VecDeque<usize>) {
+pub struct Day22;
 
-    let deck1: VecDeque<usize> = lines
-    .by_ref()
-    .take_while(|line| line != "")
-    .skip(1)
-    .map(|line| line.parse().unwrap())
-    .collect();
+impl Solver for Day22 {
+    type Input = Decks;
+    const DAY: usize = 22;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: EXAMPLE, answers: [Some("306"), Some("291")] },
+    ];
 
-    let deck2: VecDeque<usize> = lines
-    .take_while(|line| line != "")
-    .skip(1)
-    .map(|line| line.parse().unwrap())
-    .collect();
+    fn parse(&self, lines: Vec<String>) -> Result<Decks> {
+        parse(&lines)
+    }
 
-    (deck1, deck2)
+    fn part1(&self, decks: &Decks) -> Result<Answer> {
+        Ok(part1::run(decks).into())
+    }
 
+    fn part2(&self, decks: &Decks) -> Result<Answer> {
+        Ok(part2::run(decks).into())
+    }
 }
+
+/// Player 1's and player 2's decks, top card first
+pub type Decks = (VecDeque<usize>, VecDeque<usize>);
+
+/// Two sections like `Player <n>:` followed by one card per line, separated by an empty line
+pub fn parse(lines: &[String]) -> Result<Decks> {
+    let sections = read_sections(lines, &["the deck of player 1", "the deck of player 2"])?;
+    Ok((parse_deck(&sections[0], 1)?, parse_deck(&sections[1], 2)?))
+}
+
+fn parse_deck(block: &Block, player: usize) -> Result<VecDeque<usize>> {
+    block.expect_header(&format!("Player {}:", player))?;
+    block
+    .body()
+    .map(|(index, line)| parse_token(index, line, line, "a card"))
+    .collect()
+}
+
+const EXAMPLE: &str =
+"Player 1:
+9
+2
+6
+3
+1
+
+Player 2:
+5
+8
+4
+7
+10
+";
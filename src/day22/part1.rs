@@ -1,8 +1,6 @@
 use std::collections::VecDeque;
 
-use crate::lib;
-
-use super::parse;
+use super::Decks;
 
 struct Game {
     deck1: VecDeque<usize>,
@@ -21,7 +19,7 @@ impl Iterator for Game {
 }
 
 /// Returns the decks after this round, or None if one of the decks is already empty
-fn next_decks((mut deck1, mut deck2): (VecDeque<usize>, VecDeque<usize>)) -> Option<(VecDeque<usize>, VecDeque<usize>)> {
+fn next_decks((mut deck1, mut deck2): Decks) -> Option<Decks> {
     let n1 = deck1.pop_front()?;
     let n2 = deck2.pop_front()?;
     if n1 > n2 {
@@ -34,12 +32,12 @@ fn next_decks((mut deck1, mut deck2): (VecDeque<usize>, VecDeque<usize>)) -> Opt
     Some((deck1, deck2))
 }
 
-fn score((deck1, deck2): (VecDeque<usize>, VecDeque<usize>)) -> usize {
+pub fn score((deck1, deck2): Decks) -> usize {
 
     let mut game = Game { deck1, deck2 };
-    while let Some(_) = game.next() {}
+    for _ in &mut game {}
 
-    if game.deck1.len() != 0 {
+    if !game.deck1.is_empty() {
         game.deck1
     } else {
         game.deck2
@@ -52,35 +50,19 @@ fn score((deck1, deck2): (VecDeque<usize>, VecDeque<usize>)) -> usize {
 
 }
 
-pub fn run(file: &str) -> std::io::Result<usize> {
-    let input = lib::read_lines(file)?;
-    Ok(score(parse(input)))
+pub fn run(decks: &Decks) -> usize {
+    score(decks.clone())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-
-    const INPUT: &str =
-"Player 1:
-9
-2
-6
-3
-1
-
-Player 2:
-5
-8
-4
-7
-10
-";
+    use super::super::{parse, EXAMPLE};
 
     #[test]
     fn run() {
-        let input = INPUT.lines().map(|s| s.to_string());
-        assert_eq!(306, score(parse(input)))
+        let input: Vec<String> = EXAMPLE.lines().map(|s| s.to_string()).collect();
+        assert_eq!(306, score(parse(&input).unwrap()))
     }
 
 }
@@ -1,12 +1,10 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::HashSet;
 
-use crate::lib;
-
-use super::parse;
+use super::Decks;
 
 struct Game {
-    decks: (VecDeque<usize>, VecDeque<usize>),
-    memory: HashSet<(VecDeque<usize>, VecDeque<usize>)>,
+    decks: Decks,
+    memory: HashSet<Decks>,
     first_wins: bool,
 }
 
@@ -36,16 +34,16 @@ impl Iterator for Game {
 
 /// Returns the decks after this round, or None if the sub-game ends,
 /// together with a boolean indicating whether first player wins the round
-fn next_decks(mut decks: (VecDeque<usize>, VecDeque<usize>)) -> (Option<(VecDeque<usize>, VecDeque<usize>)>, bool) {
+fn next_decks(mut decks: Decks) -> (Option<Decks>, bool) {
 
     // Try to get the two cards, otherwise first wins by default
     let (n1, n2) = {
         let n1 = decks.0.pop_front();
-        if n1 == None {
+        if n1.is_none() {
             return (None, false);
         }
         let n2 = decks.1.pop_front();
-        if n2 == None {
+        if n2.is_none() {
             return (None, true);
         }
         (n1.unwrap(), n2.unwrap())
@@ -56,7 +54,7 @@ fn next_decks(mut decks: (VecDeque<usize>, VecDeque<usize>)) -> (Option<(VecDequ
         let mut subgame = Game { decks: decks.clone(), first_wins: false, memory: HashSet::new() };
         subgame.decks.0 = subgame.decks.0.into_iter().take(n1).collect();
         subgame.decks.1 = subgame.decks.1.into_iter().take(n2).collect();
-        while let Some(_) = subgame.next() {}
+        for _ in &mut subgame {}
         subgame.first_wins
     } else {
         // Can't play sub-game so we resort to common Combat rules
@@ -74,10 +72,10 @@ fn next_decks(mut decks: (VecDeque<usize>, VecDeque<usize>)) -> (Option<(VecDequ
 
 }
 
-fn score(decks: (VecDeque<usize>, VecDeque<usize>)) -> usize {
+pub fn score(decks: Decks) -> usize {
 
     let mut game = Game { decks, first_wins: false, memory: HashSet::new() };
-    while let Some(_) = game.next() {}
+    for _ in &mut game {}
 
     if game.first_wins { game.decks.0 } else { game.decks.1 }
     .into_iter()
@@ -88,35 +86,19 @@ fn score(decks: (VecDeque<usize>, VecDeque<usize>)) -> usize {
 
 }
 
-pub fn run(file: &str) -> std::io::Result<usize> {
-    let input = lib::read_lines(file)?;
-    Ok(score(parse(input)))
+pub fn run(decks: &Decks) -> usize {
+    score(decks.clone())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-
-    const INPUT: &str =
-"Player 1:
-9
-2
-6
-3
-1
-
-Player 2:
-5
-8
-4
-7
-10
-";
+    use super::super::{parse, EXAMPLE};
 
     #[test]
     fn run() {
-        let input = INPUT.lines().map(|s| s.to_string());
-        assert_eq!(291, score(parse(input)))
+        let input: Vec<String> = EXAMPLE.lines().map(|s| s.to_string()).collect();
+        assert_eq!(291, score(parse(&input).unwrap()))
     }
 
 }
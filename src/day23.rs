@@ -1,8 +1,54 @@
 use std::collections::HashMap;
 
-use crate::lib;
+use crate::error::{Error, Result};
+use crate::solver::{Answer, Example, Solver};
 
-pub const INPUT_FILE: &str = "input/23.txt";
+pub struct Day23;
+
+impl Solver for Day23 {
+    /// The cup labels, clockwise
+    type Input = Vec<usize>;
+    const DAY: usize = 23;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("67384529"), Some("149245887792")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<usize>> {
+        parse_cups(lines.first().ok_or_else(|| Error::input("expected the cup labels"))?)
+    }
+
+    fn part1(&self, cups: &Vec<usize>) -> Result<Answer> {
+        Ok(play1(cups.clone(), 100).into())
+    }
+
+    fn part2(&self, cups: &Vec<usize>) -> Result<Answer> {
+        Ok(play2(cups.clone(), 10_000_000).into())
+    }
+}
+
+/// Each move handles four cups besides the destination
+const MIN_CUPS: usize = 5;
+
+/// One digit per cup, the labels being 1 to the number of cups in some order
+pub fn parse_cups(line: &str) -> Result<Vec<usize>> {
+    let cups = line
+    .chars()
+    .enumerate()
+    .map(|(column, c)| match c.to_digit(10) {
+        Some(digit) => Ok(digit as usize),
+        None => Err(Error::parse(0, line, format!("expected a cup label, found {:?}", c)).at(Some(column + 1))),
+    })
+    .collect::<Result<Vec<usize>>>()?;
+    if cups.len() < MIN_CUPS {
+        return Err(Error::parse(0, line, format!("expected at least {} cups", MIN_CUPS)));
+    }
+    for label in 1..=cups.len() {
+        if !cups.contains(&label) {
+            return Err(Error::parse(0, line, format!("the labels must go from 1 to {}, but {} is missing", cups.len(), label)));
+        }
+    }
+    Ok(cups)
+}
 
 #[derive(Debug)]
 struct Game {
@@ -50,8 +96,7 @@ fn next(mut game: Game) -> Game {
 
 }
 
-fn play1(input: String, moves: usize) -> String {
-    let cups: Vec<usize> = input.chars().map(|c| c.to_string().parse().unwrap()).collect();
+pub fn play1(cups: Vec<usize>, moves: usize) -> String {
     let current = cups[0];
     let cups = cups.clone().into_iter().zip(cups.into_iter().cycle().skip(1)).collect();
     let mut game = Game { cups, current };
@@ -67,8 +112,9 @@ fn play1(input: String, moves: usize) -> String {
     .collect()
 }
 
-fn play2(input: String, moves: usize) -> usize {
-    let cups: Vec<usize> = input.chars().map(|c| c.to_string().parse().unwrap()).chain(10..=1_000_000).collect();
+pub fn play2(cups: Vec<usize>, moves: usize) -> usize {
+    let labels = cups.len();
+    let cups: Vec<usize> = cups.into_iter().chain(labels + 1..=1_000_000).collect();
     let current = cups[0];
     let cups = cups.clone().into_iter().zip(cups.into_iter().cycle().skip(1)).collect();
     let mut game = Game { cups, current };
@@ -83,37 +129,27 @@ fn play2(input: String, moves: usize) -> usize {
     .product()
 }
 
-pub fn run1(file: &str) -> std::io::Result<String> {
-    let input = lib::read_lines(file)?.next().unwrap();
-    Ok(play1(input, 100))
-}
-
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    let input = lib::read_lines(file)?.next().unwrap();
-    Ok(play2(input, 10_000_000))
-}
+const INPUT_1: &str = "389125467";
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const INPUT_1: &str = "389125467";
-
     #[test]
     fn run1_10() {
-        let input = INPUT_1.lines().map(|s| s.to_string()).next().unwrap();
+        let input = parse_cups(INPUT_1).unwrap();
         assert_eq!("92658374", play1(input, 10));
     }
 
     #[test]
     fn run1_100() {
-        let input = INPUT_1.lines().map(|s| s.to_string()).next().unwrap();
+        let input = parse_cups(INPUT_1).unwrap();
         assert_eq!("67384529", play1(input, 100));
     }
 
     #[test]
     fn run2() {
-        let input = INPUT_1.lines().map(|s| s.to_string()).next().unwrap();
+        let input = parse_cups(INPUT_1).unwrap();
         assert_eq!(149245887792, play2(input, 10_000_000));
     }
 
@@ -1,8 +1,31 @@
 use std::collections::HashSet;
 
-use crate::lib;
+use crate::automaton::{Automaton, Hex, Rule, Sparse};
+use crate::error::{Error, Result};
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day24;
+
+impl Solver for Day24 {
+    /// The tiles that are black after following every line of the input
+    type Input = HashSet<Coord>;
+    const DAY: usize = 24;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("10"), Some("2208")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<HashSet<Coord>> {
+        black(&lines)
+    }
+
+    fn part1(&self, black: &HashSet<Coord>) -> Result<Answer> {
+        Ok(black.len().into())
+    }
 
-pub const INPUT_FILE: &str = "input/24.txt";
+    fn part2(&self, black: &HashSet<Coord>) -> Result<Answer> {
+        Ok(flip_n(black.clone(), 100).len().into())
+    }
+}
 
 /// We use three axis hexagonal coordinates
 /// With this system, when moving in any of the three axis of neighbor squares, not one but two coordinates change; the third is invariant.
@@ -11,9 +34,9 @@ pub const INPUT_FILE: &str = "input/24.txt";
 /// s: invariant in the secondary diagonal
 /// h: invariant in the horizontal
 #[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
-struct Coord(isize, isize, isize);
+pub struct Coord(pub isize, pub isize, pub isize);
 
-enum Direction {
+pub enum Direction {
     W,
     E,
     NW,
@@ -22,7 +45,7 @@ enum Direction {
     SE,
 }
 
-fn walk(Coord(m, s, h): Coord, direction: Direction) -> Coord {
+pub fn walk(Coord(m, s, h): Coord, direction: Direction) -> Coord {
     use Direction::*;
     match direction {
         W => Coord(m - 1, s + 1, h),
@@ -34,108 +57,58 @@ fn walk(Coord(m, s, h): Coord, direction: Direction) -> Coord {
     }
 }
 
-fn coordinate(directions: Vec<Direction>) -> Coord {
+pub fn coordinate(directions: Vec<Direction>) -> Coord {
     directions.into_iter()
     .fold(Coord(0, 0, 0), walk)
 }
 
-fn parse(line: String) -> Vec<Direction> {
-    let mut chars = line.chars();
+pub fn parse(index: usize, line: &str) -> Result<Vec<Direction>> {
+    let error = |column: usize| Error::parse(index, line, "expected one of e, se, sw, w, nw or ne").at(Some(column + 1));
+    let mut chars = line.chars().enumerate();
     let mut directions = vec![];
-    while let Some(c) = chars.next() {
+    while let Some((column, c)) = chars.next() {
         directions.push(match c {
             'n' => match chars.next() {
-                Some('e') => Direction::NE,
-                Some('w') => Direction::NW,
-                _ => panic!(),
+                Some((_, 'e')) => Direction::NE,
+                Some((_, 'w')) => Direction::NW,
+                _ => return Err(error(column)),
             },
             's' => match chars.next() {
-                Some('e') => Direction::SE,
-                Some('w') => Direction::SW,
-                _ => panic!(),
+                Some((_, 'e')) => Direction::SE,
+                Some((_, 'w')) => Direction::SW,
+                _ => return Err(error(column)),
             },
             'e' => Direction::E,
             'w' => Direction::W,
-            _ => panic!(),
+            _ => return Err(error(column)),
         });
     }
-    directions
+    Ok(directions)
 }
 
-fn black<T: Iterator<Item=String>>(lines: T) -> HashSet<Coord> {
-    lines
-    .map(parse)
-    .map(coordinate)
-    .fold(HashSet::new(), |mut black, coord| {
+pub fn black(lines: &[String]) -> Result<HashSet<Coord>> {
+    let mut black = HashSet::new();
+    for (index, line) in lines.iter().enumerate() {
+        let coord = coordinate(parse(index, line)?);
         if !black.remove(&coord) {
             black.insert(coord);
         }
-        black
-    })
-}
-
-fn neighbors(coord: Coord) -> Vec<Coord> {
-    use Direction::*;
-    vec![
-        W,
-        E,
-        NW,
-        NE,
-        SW,
-        SE,
-    ].into_iter()
-    .map(|direction| walk(coord, direction))
-    .collect()
-}
-
-fn flip(black: HashSet<Coord>) -> HashSet<Coord> {
-
-    let candidates: Vec<Coord> = black.clone().into_iter()
-    .map(|coord| neighbors(coord))
-    .flatten()
-    .chain(black.clone().into_iter())
-    .collect();
-
-    candidates.into_iter()
-    .fold(HashSet::new(), |mut new_black, coord| {
-        let black_neighbors = neighbors(coord).into_iter()
-        .filter(|neighbor| black.contains(neighbor))
-        .count();
-
-        if match black_neighbors {
-            1 => black.contains(&coord),
-            2 => true,
-            _ => false,
-        } {
-            new_black.insert(coord);
-        }
-        new_black
-    })
-
-}
-
-fn flip_n(mut black: HashSet<Coord>, times: usize) -> HashSet<Coord> {
-    for _ in 0..times {
-        black = flip(black);
     }
-    black
+    Ok(black)
 }
 
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    let input = lib::read_lines(file)?;
-    Ok(black(input).len())
-}
+/// A white tile with 2 black neighbours becomes black, and a black tile stays black with 1 or 2
+pub const RULE: Rule = Rule::new(&[2], &[1, 2]);
 
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    let input = lib::read_lines(file)?;
-    Ok(flip_n(black(input), 100).len())
+/// The black tiles after flipping them for the given number of days
+pub fn flip_n(black: HashSet<Coord>, times: usize) -> HashSet<Coord> {
+    // Tiles are hexagons in axial coordinates, where m and h are enough to tell them apart
+    let mut floor = Sparse::new(Hex, RULE, black.into_iter().map(|Coord(m, _, h)| (m, h)));
+    floor.run(times);
+    floor.alive().iter().map(|&(m, h)| Coord(m, -m - h, h)).collect()
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    const INPUT_1: &str =
+const INPUT_1: &str =
 "sesenwnenenewseeswwswswwnenewsewsw
 neeenesenwnwwswnenewnwwsewnenwseswesw
 seswneswswsenwwnwse
@@ -158,16 +131,20 @@ neswnwewnwnwseenwseesewsenwsweewe
 wseweeenwnesenwwwswnew
 ";
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
     #[test]
     fn run1() {
-        let input = INPUT_1.lines().map(|s| s.to_string());
-        assert_eq!(10, black(input).len());
+        let input: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        assert_eq!(10, black(&input).unwrap().len());
     }
 
     #[test]
     fn run2() {
-        let input = INPUT_1.lines().map(|s| s.to_string());
-        assert_eq!(2208, flip_n(black(input), 100).len());
+        let input: Vec<String> = INPUT_1.lines().map(|s| s.to_string()).collect();
+        assert_eq!(2208, flip_n(black(&input).unwrap(), 100).len());
     }
 
 }
\ No newline at end of file
@@ -1,17 +1,50 @@
-use crate::lib;
+use crate::error::{Error, Result, parse_token};
+use crate::solver::{Answer, Example, Solver};
+
+const MODULUS: usize = 20201227;
+
+pub struct Day25;
+
+impl Solver for Day25 {
+    /// The card's and the door's public keys
+    type Input = (usize, usize);
+    const DAY: usize = 25;
+    const PARTS: usize = 1;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("14897079"), None] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<(usize, usize)> {
+        match lines.as_slice() {
+            [card, door] => Ok((parse_key(0, card)?, parse_key(1, door)?)),
+            _ => Err(Error::input("expected two public keys, one per line")),
+        }
+    }
 
-pub const INPUT_FILE: &str = "input/25.txt";
+    fn part1(&self, &(card, door): &(usize, usize)) -> Result<Answer> {
+        Ok(encryption_key(card, door).into())
+    }
+}
 
-fn step(value: usize, subject_number: usize) -> usize {
-    (value * subject_number) % 20201227
+/// Any number between 1 and the modulus can be reached from 7, so the loop size of the key exists
+pub fn parse_key(index: usize, line: &str) -> Result<usize> {
+    let key = parse_token(index, line, line, "a public key")?;
+    if key == 0 || key >= MODULUS {
+        return Err(Error::parse(index, line, format!("public keys must be between 1 and {}", MODULUS - 1)));
+    }
+    Ok(key)
 }
 
-fn transform(subject_number: usize, loop_size: usize) -> usize {
+pub fn step(value: usize, subject_number: usize) -> usize {
+    (value * subject_number) % MODULUS
+}
+
+pub fn transform(subject_number: usize, loop_size: usize) -> usize {
     (0..loop_size)
     .fold(1, |value, _| step(value, subject_number))
 }
 
-fn loop_size(public_key: usize) -> usize {
+pub fn loop_size(public_key: usize) -> usize {
     let mut value = 1;
     let mut loop_size = 0;
     while value != public_key {
@@ -21,27 +54,21 @@ fn loop_size(public_key: usize) -> usize {
     loop_size
 }
 
-fn encryption_key(a_public_key: usize, b_public_key: usize) -> usize {
+pub fn encryption_key(a_public_key: usize, b_public_key: usize) -> usize {
 
     let a_loop_size = loop_size(a_public_key);
     transform(b_public_key, a_loop_size)
 
 }
 
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    let input: Vec<usize> = lib::read_lines(file)?.map(|s| s.parse().unwrap()).collect();
-    Ok(encryption_key(input[0], input[1]))
-}
-
+const INPUT_1: &str =
+"5764801
+17807724";
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const INPUT_1: &str =
-"5764801
-17807724";
-
     #[test]
     fn run1() {
 
@@ -1,74 +1,82 @@
-use crate::lib;
+use crate::Grid;
+use crate::error::Result;
+use crate::solver::{Answer, Example, Solver};
 
-pub const INPUT_FILE: &str = "input/3.txt";
+pub struct Day3;
 
-fn count_trees(lines: impl Iterator<Item=String>, right: usize, down: usize) -> usize {
-    let mut position: usize = 0;
-    lines
+impl Solver for Day3 {
+    /// `true` where there is a tree
+    type Input = Grid<bool>;
+    const DAY: usize = 3;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: EXAMPLE, answers: [Some("7"), Some("336")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Grid<bool>> {
+        parse(&lines)
+    }
+
+    fn part1(&self, grid: &Grid<bool>) -> Result<Answer> {
+        Ok(count_trees(grid, 3, 1).into())
+    }
+
+    fn part2(&self, grid: &Grid<bool>) -> Result<Answer> {
+        Ok(count_trees_multislope(grid).into())
+    }
+}
+
+pub fn parse(lines: &[String]) -> Result<Grid<bool>> {
+    Grid::parse(lines, |c| match c {
+        '.' => Some(false),
+        '#' => Some(true),
+        _ => None,
+    })
+}
+
+/// The pattern repeats to the right, so the grid wraps horizontally
+pub fn count_trees(grid: &Grid<bool>, right: usize, down: usize) -> usize {
+    (0..grid.height())
         .step_by(down)
-        .filter(|line| {
-            let is_tree = line.chars().nth(position % line.len()).unwrap() == '#';
-            position += right;
-            is_tree
-        })
+        .enumerate()
+        .filter(|&(step, y)| *grid.get_wrapping(((step * right) as isize, y as isize)))
         .count()
 }
 
-fn count_trees_multislope(lines: impl Iterator<Item=String>) -> usize {
-    let lines: Vec<String> = lines.collect();
+pub fn count_trees_multislope(grid: &Grid<bool>) -> usize {
     vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
         .into_iter()
-        .map(|(right, down)| count_trees(lines.clone().into_iter(), right, down))
+        .map(|(right, down)| count_trees(grid, right, down))
         .product()
 }
 
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    Ok(count_trees(lib::read_lines(file)?, 3, 1))
-}
-
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    Ok(count_trees_multislope(lib::read_lines(file)?))
-}
+const EXAMPLE: &str =
+"..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
 
 #[cfg(test)]
 mod test {
 
-    use super::{count_trees, count_trees_multislope};
+    use super::{count_trees, count_trees_multislope, parse, EXAMPLE};
 
     #[test]
     fn run1() {
-
-        let lines = "..##.......
-                          #...#...#..
-                          .#....#..#.
-                          ..#.#...#.#
-                          .#...##..#.
-                          ..#.##.....
-                          .#.#.#....#
-                          .#........#
-                          #.##...#...
-                          #...##....#
-                          .#..#...#.#";
-
-        assert_eq!(count_trees(lines.split('\n').map(|s| s.trim().to_string()), 3, 1), 7);
+        let lines: Vec<String> = EXAMPLE.lines().map(|s| s.to_string()).collect();
+        assert_eq!(count_trees(&parse(&lines).unwrap(), 3, 1), 7);
     }
 
     #[test]
     fn run2() {
-
-        let lines = "..##.......
-                          #...#...#..
-                          .#....#..#.
-                          ..#.#...#.#
-                          .#...##..#.
-                          ..#.##.....
-                          .#.#.#....#
-                          .#........#
-                          #.##...#...
-                          #...##....#
-                          .#..#...#.#";
-
-        assert_eq!(count_trees_multislope(lines.split('\n').map(|s| s.trim().to_string())), 336);
+        let lines: Vec<String> = EXAMPLE.lines().map(|s| s.to_string()).collect();
+        assert_eq!(count_trees_multislope(&parse(&lines).unwrap()), 336);
     }
 
 }
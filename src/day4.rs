@@ -1,6 +1,33 @@
-use crate::lib;
+use crate::error::{Error, Result};
+use crate::read_blocks;
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day4;
+
+impl Solver for Day4 {
+    type Input = Vec<Passport>;
+    const DAY: usize = 4;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT_1, answers: [Some("2"), None] },
+        Example { input: INPUT_2_INVALID, answers: [None, Some("0")] },
+        Example { input: INPUT_2_VALID, answers: [None, Some("4")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<Passport>> {
+        parse_passports(&lines)
+    }
+
+    fn part1(&self, passports: &Vec<Passport>) -> Result<Answer> {
+        Ok(count_passports(passports, true).into())
+    }
+
+    fn part2(&self, passports: &Vec<Passport>) -> Result<Answer> {
+        Ok(count_passports(passports, false).into())
+    }
+}
 
-pub const INPUT_FILE: &str = "input/4.txt";
+/// The (key, value) pairs of a passport, in order of appearance
+pub type Passport = Vec<(String, String)>;
 
 pub fn count_fields(fields: impl Iterator<Item=(String, String)>, one: bool) -> usize {
     if one {
@@ -24,7 +51,7 @@ pub fn count_fields2(fields: impl Iterator<Item=(String, String)>) -> usize {
                 return false;
             }
             if let Ok(number) = value.clone().parse::<u16>() {
-                return 1920 <= number && number <= 2002;
+                return (1920..=2002).contains(&number);
             }
             false
         },
@@ -33,7 +60,7 @@ pub fn count_fields2(fields: impl Iterator<Item=(String, String)>) -> usize {
                 return false;
             }
             if let Ok(number) = value.clone().parse::<u16>() {
-                return 2010 <= number && number <= 2020;
+                return (2010..=2020).contains(&number);
             }
             false
         },
@@ -42,7 +69,7 @@ pub fn count_fields2(fields: impl Iterator<Item=(String, String)>) -> usize {
                 return false;
             }
             if let Ok(number) = value.clone().parse::<u16>() {
-                2020 <= number && number <= 2030
+                (2020..=2030).contains(&number)
             } else {
                 false
             }
@@ -51,13 +78,13 @@ pub fn count_fields2(fields: impl Iterator<Item=(String, String)>) -> usize {
             let len = value.len();
             if value.ends_with("cm") {
                 if let Ok(number) = value[..len-2].parse::<usize>() {
-                    150 <= number && number <= 193
+                    (150..=193).contains(&number)
                 } else {
                     false
                 }
             } else if value.ends_with("in") {
                 if let Ok(number) = value[..len-2].parse::<usize>() {
-                    59 <= number && number <= 76
+                    (59..=76).contains(&number)
                 } else {
                     false
                 }
@@ -67,64 +94,50 @@ pub fn count_fields2(fields: impl Iterator<Item=(String, String)>) -> usize {
         },
         "hcl" => {
             let mut chars = value.chars();
-            value.len() == 7 && chars.next().unwrap() == '#' && chars.all(|c| match c {
-                '0'..='9' | 'a'..='f' => true,
-                _ => false,
-            })
-        },
-        "ecl" => match value.as_str() {
-            "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => true,
-            _ => false
+            value.len() == 7 && chars.next().unwrap() == '#' && chars.all(|c| matches!(c, '0'..='9' | 'a'..='f'))
         },
-        "pid" => value.chars().count() == 9 && value.chars().all(|c| match c {
-            '0'..='9' => true,
-            _ => false
-        }),
+        "ecl" => matches!(value.as_str(), "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth"),
+        "pid" => value.chars().count() == 9 && value.chars().all(|c| c.is_ascii_digit()),
         "cid" => false,
         _ => false,
     })
     .count()
 }
 
-pub fn extract_fields(line: &String) -> Vec<(String, String)> {
+pub fn extract_fields(index: usize, line: &str) -> Result<Vec<(String, String)>> {
     line
-    .split(" ")
-    .filter(|&entry| entry != "")
+    .split(' ')
+    .filter(|&entry| !entry.is_empty())
     .map(|field| {
-        let mut key_value = field.split(":").map(|piece| piece.to_string());
-        let key = key_value.next();
-        let value = key_value.next();
-        (key.unwrap(), value.unwrap())
+        let (key, value) = field
+        .split_once(':')
+        .ok_or_else(|| Error::token(index, line, field, "expected `<key>:<value>`"))?;
+        Ok((key.to_string(), value.to_string()))
     })
     .collect()
 }
 
-pub fn count_passports(lines: Vec<String>, one: bool) -> usize {
-    lines
-        .split(|line| line == "")
-        .filter(|passport_lines| {
-            7 == passport_lines
-            .into_iter()
-            .map(|line| count_fields(extract_fields(line).into_iter(), one))
-            .sum::<usize>()
-        })
-        .count()
-}
-
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    Ok(count_passports(lib::read_lines(file)?.collect(), true))
+/// Passports are separated by empty lines, and their fields may span several lines
+pub fn parse_passports(lines: &[String]) -> Result<Vec<Passport>> {
+    read_blocks(lines)
+    .map(|block| {
+        let mut passport = vec![];
+        for (index, line) in block.lines() {
+            passport.extend(extract_fields(index, line)?);
+        }
+        Ok(passport)
+    })
+    .collect()
 }
 
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    Ok(count_passports(lib::read_lines(file)?.collect(), false))
+pub fn count_passports(passports: &[Passport], one: bool) -> usize {
+    passports
+        .iter()
+        .filter(|fields| 7 == count_fields(fields.iter().cloned(), one))
+        .count()
 }
 
-#[cfg(test)]
-mod test {
-    use super::count_passports;
-
-
-    const INPUT_1: &str =
+const INPUT_1: &str =
 "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
 byr:1937 iyr:2017 cid:147 hgt:183cm
 
@@ -139,12 +152,7 @@ hgt:179cm
 hcl:#cfa07d eyr:2025 pid:166559648
 iyr:2011 ecl:brn hgt:59in";
 
-    #[test]
-    fn run1() {
-        assert_eq!(2, count_passports(INPUT_1.to_string().lines().map(|line| line.to_string()).collect(), true));
-    }
-
-    const INPUT_2_INVALID: &str =
+const INPUT_2_INVALID: &str =
 "eyr:1972 cid:100
 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
 
@@ -159,12 +167,7 @@ hgt:59cm ecl:zzz
 eyr:2038 hcl:74454a iyr:2023
 pid:3556412378 byr:2007";
 
-    #[test]
-    fn run2_invalid() {
-        assert_eq!(0, count_passports(INPUT_2_INVALID.to_string().lines().map(|line| line.to_string()).collect(), false));
-    }
-
-    const INPUT_2_VALID: &str =
+const INPUT_2_VALID: &str =
 "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
 hcl:#623a2f
 
@@ -178,10 +181,23 @@ eyr:2022
 
 iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
 
+#[cfg(test)]
+mod test {
+    use super::{count_passports, parse_passports, INPUT_1, INPUT_2_INVALID, INPUT_2_VALID};
+
     #[test]
-    fn run2_valid() {
-        assert_eq!(4, count_passports(INPUT_2_VALID.to_string().lines().map(|line| line.to_string()).collect(), false));
+    fn run1() {
+        assert_eq!(2, count_passports(&parse_passports(&INPUT_1.to_string().lines().map(|line| line.to_string()).collect::<Vec<_>>()).unwrap(), true));
     }
 
+    #[test]
+    fn run2_invalid() {
+        assert_eq!(0, count_passports(&parse_passports(&INPUT_2_INVALID.to_string().lines().map(|line| line.to_string()).collect::<Vec<_>>()).unwrap(), false));
+    }
+
+    #[test]
+    fn run2_valid() {
+        assert_eq!(4, count_passports(&parse_passports(&INPUT_2_VALID.to_string().lines().map(|line| line.to_string()).collect::<Vec<_>>()).unwrap(), false));
+    }
 
 }
\ No newline at end of file
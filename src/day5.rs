@@ -1,8 +1,40 @@
-use crate::lib;
+use crate::error::{Error, Result};
+use crate::solver::{Answer, Example, Solver};
 
-pub const INPUT_FILE: &str = "input/5.txt";
+pub struct Day5;
+
+impl Solver for Day5 {
+    /// Seat ids of the boarding passes
+    type Input = Vec<u64>;
+    const DAY: usize = 5;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: EXAMPLE, answers: [Some("820"), None] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<u64>> {
+        lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| seat_id(line).ok_or_else(|| {
+            Error::parse(index, line, "expected 7 of F or B followed by 3 of L or R")
+        }))
+        .collect()
+    }
+
+    fn part1(&self, seats: &Vec<u64>) -> Result<Answer> {
+        seats.iter().max().map(|&id| id.into()).ok_or(Error::NoSolution)
+    }
+
+    fn part2(&self, seats: &Vec<u64>) -> Result<Answer> {
+        missing_seat_id(seats).map(Answer::from).ok_or(Error::NoSolution)
+    }
+}
+
+pub fn seat_id(seat: &str) -> Option<u64> {
+    if seat.len() != 10 || !seat.chars().all(|c| "FBLR".contains(c)) {
+        return None;
+    }
 
-fn seat_id(seat: &str) -> Option<u64> {
     let row_string = &seat[..7]
     .replace("F", "0")
     .replace("B", "1");
@@ -16,22 +48,18 @@ fn seat_id(seat: &str) -> Option<u64> {
     Some(row * 8 + column)
 }
 
-pub fn run1(file: &str) -> Option<u64> {
-    lib::read_lines(file).ok()?
-    .map(|line| seat_id(line.as_str()))
-    .max()?
-}
-
-pub fn run2(file: &str) -> Option<u64> {
-    let seats: Vec<u64> = lib::read_lines(file).ok()?
-    .map(|line| seat_id(line.as_str()))
-    .collect::<Option<Vec<u64>>>()?;
+pub fn missing_seat_id(seats: &[u64]) -> Option<u64> {
     (8..127*8).find(|&id|
         !seats.contains(&id) && // Not in the list
         seats.contains(&(id - 1)) && seats.contains(&(id + 1)) // Both previous and next ids are present
     )
 }
 
+const EXAMPLE: &str =
+"BFFFBBFRRR
+FFFBBBFRRR
+BBFFBBFRLL";
+
 #[cfg(test)]
 mod test {
     use super::seat_id;
@@ -52,7 +80,7 @@ mod test {
     #[test]
     fn run1() {
         let computed_ids: Vec<u64> = SEATS.iter().map(|&seat| seat_id(seat).unwrap_or(0)).collect();
-        assert_eq!(computed_ids, IDS.iter().cloned().collect::<Vec<u64>>());
+        assert_eq!(computed_ids, IDS.to_vec());
     }
 
 }
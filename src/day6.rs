@@ -1,11 +1,34 @@
 use std::collections::BTreeSet;
 
-use crate::lib;
+use crate::error::Result;
+use crate::read_blocks;
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day6;
+
+impl Solver for Day6 {
+    type Input = Vec<String>;
+    const DAY: usize = 6;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT, answers: [Some("11"), Some("6")] },
+    ];
+
+    /// Any character counts as a question, so there is nothing to check
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<String>> {
+        Ok(lines)
+    }
+
+    fn part1(&self, lines: &Vec<String>) -> Result<Answer> {
+        Ok(sum_questions(lines, true).into())
+    }
 
-pub const INPUT_FILE: &str = "input/6.txt";
+    fn part2(&self, lines: &Vec<String>) -> Result<Answer> {
+        Ok(sum_questions(lines, false).into())
+    }
+}
 
-fn questions_any(lines: &[String]) -> usize {
-    lines.into_iter()
+pub fn questions_any(lines: &[&str]) -> usize {
+    lines.iter()
     .fold(BTreeSet::new(), |mut set, line| {
         line.chars().for_each(|question| { set.insert(question); });
         set
@@ -13,8 +36,8 @@ fn questions_any(lines: &[String]) -> usize {
     .len()
 }
 
-fn questions_all(lines: &[String]) -> usize {
-    let mut sets = lines.into_iter()
+pub fn questions_all(lines: &[&str]) -> usize {
+    let mut sets = lines.iter()
     .map(|line| {
         let mut set = BTreeSet::new();
         line.chars().for_each(|question| { set.insert(question); });
@@ -30,27 +53,17 @@ fn questions_all(lines: &[String]) -> usize {
     }
 }
 
-fn sum_questions(lines: Vec<String>, any: bool) -> usize {
-    lines
-    .split(|line| line == "")
-    .map(|lines| if any { questions_any(lines) } else { questions_all(lines) })
+/// Groups are separated by empty lines, with one person per line
+pub fn sum_questions(lines: &[String], any: bool) -> usize {
+    read_blocks(lines)
+    .map(|group| {
+        let lines: Vec<&str> = group.lines().map(|(_, line)| line).collect();
+        if any { questions_any(&lines) } else { questions_all(&lines) }
+    })
     .sum()
 }
 
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    Ok(sum_questions(lib::read_lines(file)?.collect(), true))
-}
-
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    Ok(sum_questions(lib::read_lines(file)?.collect(), false))
-}
-
-#[cfg(test)]
-mod test {
-    use super::sum_questions;
-
-
-    const INPUT: &str =
+const INPUT: &str =
 "abc
 
 a
@@ -68,14 +81,18 @@ a
 b
 ";
 
+#[cfg(test)]
+mod test {
+    use super::{sum_questions, INPUT};
+
     #[test]
     fn run1() {
-        assert_eq!(11, sum_questions(INPUT.to_string().lines().map(|line| line.to_string()).collect(), true))
+        assert_eq!(11, sum_questions(&INPUT.to_string().lines().map(|line| line.to_string()).collect::<Vec<_>>(), true))
     }
 
     #[test]
     fn run2() {
-        assert_eq!(6, sum_questions(INPUT.to_string().lines().map(|line| line.to_string()).collect(), false))
+        assert_eq!(6, sum_questions(&INPUT.to_string().lines().map(|line| line.to_string()).collect::<Vec<_>>(), false))
     }
 
 }
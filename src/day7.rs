@@ -1,8 +1,33 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::lib;
+use crate::error::{Error, Result};
+use crate::solver::{Answer, Example, Solver};
 
-pub const INPUT_FILE: &str = "input/7.txt";
+pub struct Day7;
+
+pub type Graph = HashMap<String, Vec<(usize, String)>>;
+
+impl Solver for Day7 {
+    /// The graph for the first part (containee -> containers) and for the second one (container -> containees)
+    type Input = (Graph, Graph);
+    const DAY: usize = 7;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT, answers: [Some("4"), Some("32")] },
+        Example { input: INPUT_2, answers: [None, Some("126")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<(Graph, Graph)> {
+        Ok((populate(lines.iter().cloned(), true)?, populate(lines.into_iter(), false)?))
+    }
+
+    fn part1(&self, (vertices, _): &(Graph, Graph)) -> Result<Answer> {
+        Ok((walk1(vertices.clone()) - 1).into())
+    }
+
+    fn part2(&self, (_, vertices): &(Graph, Graph)) -> Result<Answer> {
+        Ok((walk2(vertices, (1, "shiny gold".to_string()), 1) - 1).into())
+    }
+}
 
 
 // plan: parse each line and extract a graph vertex out of it.
@@ -17,11 +42,13 @@ pub const INPUT_FILE: &str = "input/7.txt";
 // drab plum bags contain 5 clear turquoise bags, 5 striped aqua bags, 4 dotted gold bags, 4 plaid chartreuse bags.
 // dark aqua bags contain 3 striped white bags.
 // posh black bags contain no other bags.
-fn process_line(mut vertices: HashMap<String, Vec<(usize, String)>>, line: String, first: bool) -> HashMap<String, Vec<(usize, String)>> {
+fn process_line(mut vertices: Graph, (index, line): (usize, String), first: bool) -> Result<Graph> {
+
+    let expected = || Error::parse(index, &line, "expected `<color> bags contain <n> <color> bags, ...`");
 
     // Get rid of empty bags
     if line.contains("no other bags") {
-        return vertices;
+        return Ok(vertices);
     }
 
     let simple = line
@@ -29,50 +56,46 @@ fn process_line(mut vertices: HashMap<String, Vec<(usize, String)>>, line: Strin
         .replace(" bag", "")
         .replace(".", "");
 
-    let mut words = simple
-        .split(" contain ")
-        .map(|s| s.to_string());
+    let (container, contents) = simple.split_once(" contain ").ok_or_else(expected)?;
 
-    let container = words.next().unwrap();
-    let contents = words.next().unwrap();
+    let contents = contents
+        .split(", ")
+        .map(|bags| {
+            let (number, containee) = bags.split_once(' ').ok_or_else(expected)?;
+            let number = number.parse::<usize>().map_err(|_| expected())?;
+            Ok((number, containee))
+        })
+        .collect::<Result<Vec<_>>>()?;
 
     if first {
 
-        for bags in contents.split(", ") {
-            let mut number_containee = bags.splitn(2, " ");
-            let number = number_containee.next().unwrap().parse::<usize>().unwrap();
-            let containee = number_containee.next().unwrap();
-            let mut neighbors = vertices.remove(containee).unwrap_or(vec![]);
+        for (number, containee) in contents {
+            let mut neighbors = vertices.remove(containee).unwrap_or_default();
             neighbors.push((number, container.to_string()));
             vertices.insert(containee.to_string(), neighbors);
         }
 
     } else {
 
-        let mut neighbors: Vec<(usize, String)> = vec![];
-
-        for bags in contents.split(", ") {
-            let mut number_containee = bags.splitn(2, " ");
-            let number = number_containee.next().unwrap().parse::<usize>().unwrap();
-            let containee = number_containee.next().unwrap();
-            neighbors.push((number, containee.to_string()));
-        }
+        let neighbors: Vec<(usize, String)> = contents
+            .into_iter()
+            .map(|(number, containee)| (number, containee.to_string()))
+            .collect();
 
         vertices.insert(container.to_string(), neighbors);
 
-
     }
 
-    vertices
+    Ok(vertices)
 
 }
 
-fn populate(lines: impl Iterator<Item=String>, first: bool) -> HashMap<String, Vec<(usize, String)>> {
-    lines.fold(HashMap::new(), |acc, cur| process_line(acc, cur, first))
+pub fn populate(lines: impl Iterator<Item=String>, first: bool) -> Result<Graph> {
+    lines.enumerate().try_fold(HashMap::new(), |acc, cur| process_line(acc, cur, first))
 }
 
 /// Count the amount of vertices that can be reached starting at "shiny gold"
-fn walk1(vertices: HashMap<String, Vec<(usize, String)>>) -> usize {
+pub fn walk1(vertices: Graph) -> usize {
 
     let mut visited: HashSet<String> = HashSet::new();
 
@@ -83,7 +106,7 @@ fn walk1(vertices: HashMap<String, Vec<(usize, String)>>) -> usize {
             continue;
         }
         if let Some(neighbors) = vertices.get(&current) {
-            stack.extend(neighbors.into_iter().map(|(_, name)| name).cloned().collect::<Vec<String>>());
+            stack.extend(neighbors.iter().map(|(_, name)| name).cloned().collect::<Vec<String>>());
         }
         visited.insert(current);
     }
@@ -92,33 +115,21 @@ fn walk1(vertices: HashMap<String, Vec<(usize, String)>>) -> usize {
 
 }
 
-fn walk2(vertices: &HashMap<String, Vec<(usize, String)>>, vertex: (usize, String), accumulative: usize) -> usize {
+pub fn walk2(vertices: &Graph, vertex: (usize, String), accumulative: usize) -> usize {
 
     let (vertex_number, vertex_name) = vertex;
 
     let current = vertex_number * accumulative;
 
     current + if let Some(neighbors) = vertices.get(&vertex_name) {
-        neighbors.into_iter().cloned().map(|n| walk2(vertices, n.clone(), current)).sum()
+        neighbors.iter().map(|n| walk2(vertices, n.clone(), current)).sum()
     } else {
         0
     }
 
 }
 
-pub fn run1(file: &str) -> std::io::Result<usize> {
-    Ok(walk1(populate(lib::read_lines(file)?, true)) - 1)
-}
-
-pub fn run2(file: &str) -> std::io::Result<usize> {
-    Ok(walk2(&populate(lib::read_lines(file)?, false), (1, "shiny gold".to_string()), 1) - 1)
-}
-
-#[cfg(test)]
-mod test {
-    use super::{walk1, walk2, populate};
-
-    const INPUT: &str =
+const INPUT: &str =
 "light red bags contain 1 bright white bag, 2 muted yellow bags.
 dark orange bags contain 3 bright white bags, 4 muted yellow bags.
 bright white bags contain 1 shiny gold bag.
@@ -129,17 +140,7 @@ vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
 faded blue bags contain no other bags.
 dotted black bags contain no other bags.";
 
-    #[test]
-    fn run1() {
-        assert_eq!(4, walk1(populate(INPUT.lines().map(|s| s.to_string()), true)) - 1);
-    }
-
-    #[test]
-    fn run2() {
-        assert_eq!(32, walk2(&populate(INPUT.lines().map(|s| s.to_string()), false), (1, "shiny gold".to_string()), 1) - 1);
-    }
-
-    const INPUT_2: &str =
+const INPUT_2: &str =
 "shiny gold bags contain 2 dark red bags.
 dark red bags contain 2 dark orange bags.
 dark orange bags contain 2 dark yellow bags.
@@ -148,9 +149,23 @@ dark green bags contain 2 dark blue bags.
 dark blue bags contain 2 dark violet bags.
 dark violet bags contain no other bags.";
 
+#[cfg(test)]
+mod test {
+    use super::{walk1, walk2, populate, INPUT, INPUT_2};
+
+    #[test]
+    fn run1() {
+        assert_eq!(4, walk1(populate(INPUT.lines().map(|s| s.to_string()), true).unwrap()) - 1);
+    }
+
+    #[test]
+    fn run2() {
+        assert_eq!(32, walk2(&populate(INPUT.lines().map(|s| s.to_string()), false).unwrap(), (1, "shiny gold".to_string()), 1) - 1);
+    }
+
     #[test]
     fn run2_2() {
-        assert_eq!(126, walk2(&populate(INPUT_2.lines().map(|s| s.to_string()), false), (1, "shiny gold".to_string()), 1) - 1);
+        assert_eq!(126, walk2(&populate(INPUT_2.lines().map(|s| s.to_string()), false).unwrap(), (1, "shiny gold".to_string()), 1) - 1);
     }
 
 }
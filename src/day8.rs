@@ -1,142 +1,119 @@
-use std::collections::BTreeSet;
+pub mod assembly;
+pub mod machine;
+pub mod opcodes;
+pub mod repair;
+
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::solver::{Answer, Example, Solver};
+use machine::{Machine, Termination};
+pub use opcodes::{Argument, Control, Flow, InstructionKind, InstructionSet, REGISTERS};
+
+pub struct Day8;
+
+impl Solver for Day8 {
+    type Input = Vec<Instruction>;
+    const DAY: usize = 8;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT, answers: [Some("5"), Some("8")] },
+    ];
+
+    /// The puzzle input, or a program written with labels and comments
+    fn parse(&self, lines: Vec<String>) -> Result<Vec<Instruction>> {
+        let instructions = assembly::assemble(&lines)?;
+        if instructions.is_empty() {
+            return Err(Error::input("the program is empty"));
+        }
+        Ok(instructions)
+    }
 
-use crate::lib;
+    fn part1(&self, instructions: &Vec<Instruction>) -> Result<Answer> {
+        match calculate_acc(instructions) {
+            (_, Termination::Overflow) => Err(Error::input("the accumulator overflows")),
+            (accumulator, _) => Ok(accumulator.into()),
+        }
+    }
 
-pub const INPUT_FILE: &str = "input/8.txt";
+    fn part2(&self, instructions: &Vec<Instruction>) -> Result<Answer> {
+        match calculate_acc_terminate(instructions) {
+            Ok(accumulator) => Ok(accumulator.into()),
+            Err(repair::Unrepairable::NoFlip) => Err(Error::NoSolution),
+            Err(reason) => Err(Error::input(reason.to_string())),
+        }
+    }
 
-#[derive(PartialEq, Eq, Clone, Copy)]
-enum InstructionKind {
-    Nop,
-    Acc,
-    Jmp,
+    /// Which line part 2 flips
+    fn note(&self, part: usize, instructions: &Vec<Instruction>) -> Option<String> {
+        match part {
+            2 => repair::repair(instructions).ok().map(|repair| format!("flipped {}", repair)),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Clone, Copy)]
-struct Instruction {
-    kind: InstructionKind,
-    argument: isize,
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Instruction {
+    pub kind: InstructionKind,
+    /// Index in `REGISTERS`, 0 if the kind takes no register
+    pub register: usize,
+    /// 0 if the kind takes no argument
+    pub argument: isize,
 }
 
-/// Calculate the value of the accumulator right before executing any instruction for the second time or terminating.
-/// Returns (isize, usize), where
-/// - isize is the accumulator upon finishing
-/// - usize is the line that was about to be executed
-fn calculate_acc(instructions: &Vec<Instruction>) -> (isize, usize) {
-    let mut visited: BTreeSet<usize> = BTreeSet::new();
-    let mut accumulator: isize = 0;
-    let mut current: usize = 0;
-    loop {
-
-        // Have we been here before?
-        if visited.contains(&current) {
-            return (accumulator, current);
-        }
-        visited.insert(current);
-
-        // Inscrease/Decrease accumulator
-        accumulator += match instructions[current].kind {
-            InstructionKind::Acc => instructions[current].argument,
-            _ => 0,
-        };
-
-        // Find next instruction
-        current = (current as isize + match instructions[current].kind {
-            InstructionKind::Jmp => instructions[current].argument,
-            _ => 1,
-        }) as usize;
-
-        // Exit if we're out of bounds
-        if current >= instructions.len() {
-            return (accumulator, current);
-        }
-
+impl Instruction {
+    /// An instruction that doesn't take a register
+    pub fn new(kind: InstructionKind, argument: isize) -> Instruction {
+        Instruction { kind, register: 0, argument }
     }
-}
-
-/// Get a set of the visited lines
-fn obtain_visited(instructions: &Vec<Instruction>) -> Vec<usize> {
-    let mut visited: Vec<usize> = vec![];
-    let mut current: usize = 0;
-    loop {
 
-        // Have we been here before?
-        if visited.contains(&current) {
-            return visited;
+    /// The lines the instruction at `line` may lead to, which may be outside the program
+    pub fn successors(&self, line: usize) -> Vec<isize> {
+        let (next, target) = (line as isize + 1, line as isize + self.argument);
+        match self.kind.control {
+            Control::Next => vec![next],
+            Control::Jump => vec![target],
+            Control::Branch => vec![next, target],
+            Control::Halt => vec![],
         }
-        visited.push(current);
-
-        // Find next instruction
-        current = (current as isize + match instructions[current].kind {
-            InstructionKind::Jmp => instructions[current].argument,
-            _ => 1,
-        }) as usize;
-
     }
 }
 
-fn calculate_acc_terminate(instructions: &Vec<Instruction>) -> Option<isize> {
-    let nops_and_jmps = obtain_visited(&instructions)
-    .into_iter()
-    .filter(|&line| instructions[line].kind != InstructionKind::Acc);
-
-    for instruction in nops_and_jmps {
-        let mut cloned_instructions = instructions.clone();
-        cloned_instructions[instruction] = match cloned_instructions[instruction].kind {
-            InstructionKind::Acc => cloned_instructions[instruction],
-            InstructionKind::Nop => Instruction {
-                kind: InstructionKind::Jmp,
-                argument: cloned_instructions[instruction].argument,
-            },
-            InstructionKind::Jmp => Instruction {
-                kind: InstructionKind::Nop,
-                argument: cloned_instructions[instruction].argument,
-            },
-        };
-
-        let (acc, cur) = calculate_acc(&cloned_instructions);
-
-        if cur == cloned_instructions.len() {
-            return Some(acc);
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind.name)?;
+        if self.kind.register {
+            write!(f, " {}", REGISTERS[self.register])?;
+        }
+        if self.kind.argument != Argument::None {
+            write!(f, " {:+}", self.argument)?;
         }
+        Ok(())
     }
-
-    None
-
 }
 
-fn parse_instructions(lines: impl Iterator<Item=String>) -> impl Iterator<Item=Instruction> {
-    lines.map(|line| {
-        let mut split = line.split(" ");
-        let name = split.next().unwrap();
-        let argument = split.next().unwrap().parse::<isize>().unwrap();
-        Instruction {
-            kind: match name {
-                "nop" => InstructionKind::Nop,
-                "acc" => InstructionKind::Acc,
-                "jmp" => InstructionKind::Jmp,
-                // This should not happen
-                // The appropriate thing would be to wrap the return type in an Option
-                _ => InstructionKind::Nop
-            },
-            argument
-        }
-    })
+/// Calculate the value of the accumulator right before executing any instruction for the second time or terminating,
+/// along with why the program stopped
+pub fn calculate_acc(instructions: &[Instruction]) -> (isize, Termination) {
+    let mut machine = Machine::new(instructions);
+    let termination = machine.run_until(&[Termination::Loop]);
+    (machine.accumulator(), termination)
 }
 
-pub fn run1(file: &str) -> std::io::Result<isize> {
-    Ok(calculate_acc(&parse_instructions(lib::read_lines(file)?).collect()).0)
+/// Get the visited lines, in the order they are visited
+pub fn obtain_visited(instructions: &[Instruction]) -> Vec<usize> {
+    let mut machine = Machine::new(instructions);
+    machine.start_trace();
+    machine.run_until(&[Termination::Loop]);
+    machine.trace().iter().map(|step| step.pc).collect()
 }
 
-pub fn run2(file: &str) -> Option<isize> {
-    calculate_acc_terminate(&parse_instructions(lib::read_lines(file).ok()?).collect())
+/// The accumulator once the program terminates after fixing its one corrupted instruction
+pub fn calculate_acc_terminate(instructions: &[Instruction]) -> std::result::Result<isize, repair::Unrepairable> {
+    repair::repair(instructions).map(|repair| repair.accumulator)
 }
 
-#[cfg(test)]
-mod test {
-    use super::{calculate_acc, calculate_acc_terminate, parse_instructions};
-
-
-    const INPUT: &str =
+const INPUT: &str =
 "nop +0
 acc +1
 jmp +4
@@ -148,14 +125,23 @@ jmp -4
 acc +6
 ";
 
+#[cfg(test)]
+mod test {
+    use super::{calculate_acc, calculate_acc_terminate, INPUT};
+    use super::assembly::assemble;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|s| s.to_string()).collect()
+    }
+
     #[test]
     fn run1() {
-        assert_eq!(5, calculate_acc(&parse_instructions(INPUT.lines().map(|s| s.to_string())).collect()).0);
+        assert_eq!(5, calculate_acc(&assemble(&lines(INPUT)).unwrap()).0);
     }
 
     #[test]
     fn run2() {
-        assert_eq!(Some(8), calculate_acc_terminate(&parse_instructions(INPUT.lines().map(|s| s.to_string())).collect()));
+        assert_eq!(Ok(8), calculate_acc_terminate(&assemble(&lines(INPUT)).unwrap()));
     }
 
 }
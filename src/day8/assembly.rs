@@ -0,0 +1,201 @@
+//! A text format for boot code that is easier to edit by hand than the puzzle input:
+//!
+//! ```text
+//! ; comments run from a semicolon to the end of the line
+//! start:  acc +1
+//!         jmp start      ; jumps and nops can point at a label instead of an offset
+//! ```
+//!
+//! Blank lines are ignored, a label can be on a line of its own, and a label after the last instruction is the end.
+//! The puzzle input is valid assembly, without any labels or comments.
+//! Which instructions exist and what they take is up to the `InstructionSet`, e.g. `mul b +2` with `InstructionSet::extended`.
+
+use std::collections::HashMap;
+
+use super::{Argument, Control, Instruction, InstructionKind, InstructionSet, REGISTERS};
+use crate::error::{Error, Result, parse_token};
+
+/// Name of the label that `disassemble` gives the (0-based) line `line`, or the end
+fn label(line: usize, length: usize) -> String {
+    if line == length {
+        "end".to_string()
+    } else {
+        format!("L{}", line + 1)
+    }
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whether the lines of the program can be reached from the first one
+fn reachable(program: &[Instruction]) -> Vec<bool> {
+    let mut reachable = vec![false; program.len()];
+    let mut stack = vec![0];
+    while let Some(line) = stack.pop() {
+        if line >= program.len() || reachable[line] {
+            continue;
+        }
+        reachable[line] = true;
+        stack.extend(program[line].successors(line).into_iter().filter(|&next| next >= 0).map(|next| next as usize));
+    }
+    reachable
+}
+
+/// An annotated listing of the program that `assemble` turns back into the same instructions.
+/// Jump targets get labels named after their 1-based line, each instruction is followed by its line number,
+/// and the instructions no path from the start leads to are flagged as unreachable.
+/// A nop's offset only matters once it is flipped into a jmp, so it doesn't get a label.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let target = |line: usize, instruction: &Instruction| -> Option<usize> {
+        let target = line as isize + instruction.argument;
+        let jumps = matches!(instruction.kind.control, Control::Jump | Control::Branch);
+        if jumps && target >= 0 && target as usize <= program.len() {
+            Some(target as usize)
+        } else {
+            None
+        }
+    };
+    let mut labelled = vec![false; program.len() + 1];
+    for (line, instruction) in program.iter().enumerate() {
+        if let Some(target) = target(line, instruction) {
+            labelled[target] = true;
+        }
+    }
+    let reachable = reachable(program);
+
+    let mut listing = String::new();
+    for (line, instruction) in program.iter().enumerate() {
+        let name = if labelled[line] { format!("{}:", label(line, program.len())) } else { String::new() };
+        let code = match target(line, instruction) {
+            Some(target) if instruction.kind.register => {
+                format!("{} {} {}", instruction.kind.name, REGISTERS[instruction.register], label(target, program.len()))
+            },
+            Some(target) => format!("{} {}", instruction.kind.name, label(target, program.len())),
+            None => instruction.to_string(),
+        };
+        let note = if reachable[line] { "" } else { ", unreachable" };
+        listing += &format!("{:<8}{:<16}; {}{}\n", name, code, line + 1, note);
+    }
+    if labelled[program.len()] {
+        listing += &format!("{}:\n", label(program.len(), program.len()));
+    }
+    listing
+}
+
+/// An instruction whose argument may still be a label
+struct Pending<'a> {
+    index: usize,
+    line: &'a str,
+    kind: InstructionKind,
+    register: usize,
+    argument: Option<&'a str>,
+}
+
+/// Turn assembly using the instructions of the puzzle back into instructions, resolving labels into offsets
+pub fn assemble(lines: &[String]) -> Result<Vec<Instruction>> {
+    assemble_with(&InstructionSet::boot(), lines)
+}
+
+/// Same as `assemble`, for programs using the instructions of `set`
+pub fn assemble_with(set: &InstructionSet, lines: &[String]) -> Result<Vec<Instruction>> {
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut pending = vec![];
+    for (index, line) in lines.iter().enumerate() {
+        let mut code = line.split(';').next().unwrap().trim();
+        if let Some((name, rest)) = code.split_once(':') {
+            let name = name.trim();
+            if !is_label(name) {
+                return Err(Error::token(index, line, name, format!("expected a label, found {:?}", name)));
+            }
+            if labels.insert(name, pending.len()).is_some() {
+                return Err(Error::token(index, line, name, format!("label {:?} is defined twice", name)));
+            }
+            code = rest.trim();
+        }
+        if code.is_empty() {
+            continue;
+        }
+        let mut tokens = code.split_whitespace();
+        let name = tokens.next().unwrap();
+        let kind = set.get(name).ok_or_else(|| Error::token(index, line, name, format!("unknown operation {:?}", name)))?;
+        let operands: Vec<&str> = tokens.collect();
+        if operands.len() != kind.register as usize + (kind.argument != Argument::None) as usize {
+            return Err(Error::token(index, line, code, format!("expected `{}`", kind.usage())));
+        }
+        let register = match operands.first() {
+            Some(&register) if kind.register => REGISTERS
+            .iter()
+            .position(|&name| name == register)
+            .ok_or_else(|| Error::token(index, line, register, format!("unknown register {:?}", register)))?,
+            _ => 0,
+        };
+        let argument = operands.last().cloned().filter(|_| kind.argument != Argument::None);
+        pending.push(Pending { index, line, kind, register, argument });
+    }
+
+    pending
+    .iter()
+    .enumerate()
+    .map(|(position, instruction)| {
+        let Pending { index, line, kind, register, argument } = *instruction;
+        let argument = match argument {
+            None => 0,
+            Some(label) if kind.argument == Argument::Offset && is_label(label) => {
+                let target = labels
+                .get(label)
+                .ok_or_else(|| Error::token(index, line, label, format!("undefined label {:?}", label)))?;
+                *target as isize - position as isize
+            },
+            Some(argument) => parse_token(index, line, argument, "a signed number")?,
+        };
+        Ok(Instruction { kind, register, argument })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::INPUT;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let program = assemble(&lines(INPUT)).unwrap();
+        let listing = disassemble(&program);
+        let expected = "        nop +0          ; 1
+L2:     acc +1          ; 2
+        jmp L7          ; 3
+L4:     acc +3          ; 4
+        jmp L2          ; 5
+        acc -99         ; 6, unreachable
+L7:     acc +1          ; 7
+        jmp L4          ; 8
+        acc +6          ; 9, unreachable
+";
+        assert_eq!(expected, listing);
+        assert_eq!(program, assemble(&lines(&listing)).unwrap());
+    }
+
+    #[test]
+    fn labels() {
+        let program = assemble(&lines("; count\nloop: acc +1\n\n  jmp end ; done\njmp loop\nend:")).unwrap();
+        assert_eq!("acc +1, jmp +2, jmp -2", program.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", "));
+        assert!(disassemble(&program).ends_with("end:\n"));
+        assert_eq!(vec![Instruction::new(InstructionKind::NOP, 1)], assemble(&lines("nop end\nend:")).unwrap());
+        assert!(assemble(&lines("jmp nowhere")).is_err());
+        assert!(assemble(&lines("a: nop +0\na: nop +0")).is_err());
+        assert!(assemble(&lines("a: acc a")).is_err());
+        assert!(assemble(&lines("jmp +1 +2")).is_err());
+        assert!(assemble(&lines("mul b +2")).is_err());
+        assert!(assemble_with(&InstructionSet::extended(), &lines("mul e +2")).is_err());
+        assert!(assemble_with(&InstructionSet::extended(), &lines("halt +0")).is_err());
+    }
+
+}
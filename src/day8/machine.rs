@@ -0,0 +1,173 @@
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+
+use super::{Flow, Instruction};
+
+/// Why a machine stopped running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// The pc is right after the last instruction, which is how boot code is meant to end
+    Exit,
+    /// The instruction at the pc already ran once, so the program would repeat itself forever.
+    /// That only holds if nothing but the pc decides where jumps go, which isn't the case with e.g. `jnz`.
+    Loop,
+    /// A `halt` ran
+    Halt,
+    /// The pc is neither inside the program nor right after it
+    OutOfRange,
+    /// The pc is at a breakpoint
+    Breakpoint,
+    /// The instruction at the pc would have overflowed a register
+    Overflow,
+}
+
+/// What the program did, e.g. "the program {}"
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Termination::Exit => write!(f, "runs to the end"),
+            Termination::Loop => write!(f, "loops forever"),
+            Termination::Halt => write!(f, "halts"),
+            Termination::OutOfRange => write!(f, "jumps outside of itself"),
+            Termination::Breakpoint => write!(f, "stops at a breakpoint"),
+            Termination::Overflow => write!(f, "overflows a register"),
+        }
+    }
+}
+
+/// An instruction that ran, with the accumulator right after running it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub pc: usize,
+    pub instruction: Instruction,
+    pub accumulator: isize,
+}
+
+/// The handheld game console, running a program one instruction at a time
+pub struct Machine<'a> {
+    program: &'a [Instruction],
+    /// Not a usize, since jumps can leave it before the first instruction
+    pc: isize,
+    /// As many as the program uses, the accumulator first
+    registers: Vec<isize>,
+    /// Which instructions already ran
+    visited: Vec<bool>,
+    breakpoints: HashSet<usize>,
+    /// The steps so far, if tracing
+    trace: Option<Vec<Step>>,
+}
+
+impl<'a> Machine<'a> {
+    pub fn new(program: &'a [Instruction]) -> Machine<'a> {
+        Machine {
+            program,
+            pc: 0,
+            registers: vec![0; program.iter().map(|instruction| instruction.register + 1).max().unwrap_or(1)],
+            visited: vec![false; program.len()],
+            breakpoints: HashSet::new(),
+            trace: None,
+        }
+    }
+
+    pub fn pc(&self) -> isize {
+        self.pc
+    }
+
+    pub fn accumulator(&self) -> isize {
+        self.registers[0]
+    }
+
+    pub fn registers(&self) -> &[isize] {
+        &self.registers
+    }
+
+    /// Make `run_until` stop before running the instruction at `line`
+    pub fn set_breakpoint(&mut self, line: usize) {
+        self.breakpoints.insert(line);
+    }
+
+    /// Record every step from now on
+    pub fn start_trace(&mut self) {
+        self.trace.get_or_insert_with(Vec::new);
+    }
+
+    /// The steps recorded since `start_trace`, oldest first
+    pub fn trace(&self) -> &[Step] {
+        self.trace.as_deref().unwrap_or(&[])
+    }
+
+    /// Run the instruction at the pc, even if it already ran.
+    /// Returns why it couldn't if the pc is outside the program, or `Halt` if it was a `halt`, which leaves the pc on it.
+    /// An instruction that would overflow a register leaves everything as it was and returns `Overflow`.
+    pub fn step(&mut self) -> Option<Termination> {
+        let pc = match self.pc {
+            pc if pc == self.program.len() as isize => return Some(Termination::Exit),
+            pc if pc < 0 || pc > self.program.len() as isize => return Some(Termination::OutOfRange),
+            pc => pc as usize,
+        };
+        let instruction = self.program[pc];
+        let flow = (instruction.kind.execute)(&mut self.registers, instruction.register, instruction.argument);
+        if flow == Flow::Overflow {
+            return Some(Termination::Overflow);
+        }
+        self.visited[pc] = true;
+        if let Some(trace) = &mut self.trace {
+            trace.push(Step { pc, instruction, accumulator: self.registers[0] });
+        }
+        match flow {
+            Flow::Next => self.pc += 1,
+            Flow::Jump(offset) => self.pc += offset,
+            Flow::Halt => return Some(Termination::Halt),
+            Flow::Overflow => unreachable!("returned above"),
+        }
+        None
+    }
+
+    /// Run until one of the reasons in `stop` applies, or the pc leaves the program, a `halt` runs or a register overflows,
+    /// which always stops it.
+    /// A breakpoint at the pc when this is called is ignored, so that running again resumes from it.
+    /// Without `Loop` in `stop`, a program that never ends makes this never return.
+    pub fn run_until(&mut self, stop: &[Termination]) -> Termination {
+        let mut first = true;
+        loop {
+            if let Ok(pc) = usize::try_from(self.pc) {
+                if stop.contains(&Termination::Loop) && self.visited.get(pc) == Some(&true) {
+                    return Termination::Loop;
+                }
+                if stop.contains(&Termination::Breakpoint) && !first && self.breakpoints.contains(&pc) {
+                    return Termination::Breakpoint;
+                }
+            }
+            if let Some(termination) = self.step() {
+                return termination;
+            }
+            first = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::INPUT;
+    use super::super::assembly::assemble;
+
+    #[test]
+    fn machine() {
+        let program = assemble(&INPUT.lines().map(|s| s.to_string()).collect::<Vec<_>>()).unwrap();
+        let mut machine = Machine::new(&program);
+        machine.start_trace();
+        machine.set_breakpoint(4);
+        assert_eq!(Termination::Breakpoint, machine.run_until(&[Termination::Loop, Termination::Breakpoint]));
+        assert_eq!((4, 5), (machine.pc(), machine.accumulator()));
+        assert_eq!(Termination::Loop, machine.run_until(&[Termination::Loop, Termination::Breakpoint]));
+        assert_eq!((1, 5), (machine.pc(), machine.accumulator()));
+        let pcs: Vec<usize> = machine.trace().iter().map(|step| step.pc).collect();
+        assert_eq!(vec![0, 1, 2, 6, 7, 3, 4], pcs);
+
+        let program = assemble(&["jmp +2".to_string()]).unwrap();
+        assert_eq!(Termination::OutOfRange, Machine::new(&program).run_until(&[]));
+    }
+
+}
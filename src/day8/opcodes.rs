@@ -0,0 +1,219 @@
+use std::fmt;
+
+/// Names of the registers, which instructions refer to by index. The puzzle only uses the accumulator.
+pub const REGISTERS: [&str; 5] = ["acc", "a", "b", "c", "d"];
+
+/// What an instruction takes after its name, besides an optional register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Argument {
+    None,
+    /// A signed number
+    Value,
+    /// A signed number of lines to jump by, which the assembler also accepts as a label
+    Offset,
+}
+
+/// Where the machine goes after running an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// The next line
+    Next,
+    /// This many lines away
+    Jump(isize),
+    /// Nowhere, the program is done
+    Halt,
+    /// Nowhere, a register would have overflowed, so it was left as it was
+    Overflow,
+}
+
+/// Where an instruction may go, as far as can be told without running it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    /// Always the next line
+    Next,
+    /// Always its offset away
+    Jump,
+    /// Either of those, depending on the registers
+    Branch,
+    /// Nowhere
+    Halt,
+}
+
+/// An entry of the opcode table: how an instruction is written and what it does.
+/// Two kinds are the same if they have the same name.
+#[derive(Clone, Copy)]
+pub struct InstructionKind {
+    pub name: &'static str,
+    /// Whether a register comes right after the name
+    pub register: bool,
+    pub argument: Argument,
+    /// Must match what `execute` returns, apart from `Flow::Overflow`
+    pub control: Control,
+    /// Run the instruction on the registers, given its register (0 if it takes none) and its argument (0 if none)
+    pub execute: fn(&mut [isize], usize, isize) -> Flow,
+}
+
+impl InstructionKind {
+    pub const NOP: InstructionKind = InstructionKind {
+        name: "nop",
+        register: false,
+        argument: Argument::Offset,
+        control: Control::Next,
+        execute: |_, _, _| Flow::Next,
+    };
+    pub const ACC: InstructionKind = InstructionKind {
+        name: "acc",
+        register: false,
+        argument: Argument::Value,
+        control: Control::Next,
+        execute: |registers, _, value| checked(&mut registers[0], |accumulator| accumulator.checked_add(value)),
+    };
+    pub const JMP: InstructionKind = InstructionKind {
+        name: "jmp",
+        register: false,
+        argument: Argument::Offset,
+        control: Control::Jump,
+        execute: |_, _, offset| Flow::Jump(offset),
+    };
+    pub const ADD: InstructionKind = InstructionKind {
+        name: "add",
+        register: true,
+        argument: Argument::Value,
+        control: Control::Next,
+        execute: |registers, register, value| checked(&mut registers[register], |current| current.checked_add(value)),
+    };
+    pub const MUL: InstructionKind = InstructionKind {
+        name: "mul",
+        register: true,
+        argument: Argument::Value,
+        control: Control::Next,
+        execute: |registers, register, value| checked(&mut registers[register], |current| current.checked_mul(value)),
+    };
+    /// Jump if the register isn't zero
+    pub const JNZ: InstructionKind = InstructionKind {
+        name: "jnz",
+        register: true,
+        argument: Argument::Offset,
+        control: Control::Branch,
+        execute: |registers, register, offset| if registers[register] != 0 { Flow::Jump(offset) } else { Flow::Next },
+    };
+    pub const HALT: InstructionKind = InstructionKind {
+        name: "halt",
+        register: false,
+        argument: Argument::None,
+        control: Control::Halt,
+        execute: |_, _, _| Flow::Halt,
+    };
+
+    /// How the instruction is written, e.g. `jnz <register> <offset>`
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.to_string();
+        if self.register {
+            usage += " <register>";
+        }
+        match self.argument {
+            Argument::None => {},
+            Argument::Value => usage += " <value>",
+            Argument::Offset => usage += " <offset>",
+        }
+        usage
+    }
+}
+
+/// Replace a register with what `operation` makes of it, unless that overflows
+fn checked(register: &mut isize, operation: impl Fn(isize) -> Option<isize>) -> Flow {
+    match operation(*register) {
+        Some(value) => {
+            *register = value;
+            Flow::Next
+        },
+        None => Flow::Overflow,
+    }
+}
+
+impl PartialEq for InstructionKind {
+    fn eq(&self, other: &InstructionKind) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for InstructionKind {}
+
+impl fmt::Debug for InstructionKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// The opcode table, which decides which instructions programs may use
+#[derive(Debug, Clone)]
+pub struct InstructionSet {
+    kinds: Vec<InstructionKind>,
+}
+
+impl InstructionSet {
+    /// The instructions of the puzzle
+    pub fn boot() -> InstructionSet {
+        InstructionSet { kinds: vec![InstructionKind::NOP, InstructionKind::ACC, InstructionKind::JMP] }
+    }
+
+    /// The instructions of the puzzle, along with `add`, `mul`, `jnz` and `halt`
+    pub fn extended() -> InstructionSet {
+        [InstructionKind::ADD, InstructionKind::MUL, InstructionKind::JNZ, InstructionKind::HALT]
+        .iter()
+        .fold(InstructionSet::boot(), |set, &kind| set.with(kind))
+    }
+
+    /// Add an instruction, replacing any other with the same name
+    pub fn with(mut self, kind: InstructionKind) -> InstructionSet {
+        self.kinds.retain(|&other| other != kind);
+        self.kinds.push(kind);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<InstructionKind> {
+        self.kinds.iter().find(|kind| kind.name == name).cloned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::assembly::{assemble_with, disassemble};
+    use super::super::machine::{Machine, Termination};
+
+    #[test]
+    fn extended() {
+        let source = "add acc +1\nadd a +10\nloop: mul acc +2\nadd a -1\njnz a loop\nhalt";
+        let lines: Vec<String> = source.lines().map(|s| s.to_string()).collect();
+        let program = assemble_with(&InstructionSet::extended(), &lines).unwrap();
+        let mut machine = Machine::new(&program);
+        assert_eq!(Termination::Halt, machine.run_until(&[]));
+        assert_eq!(&[1024, 0], machine.registers());
+        assert_eq!((5, 1024), (machine.pc(), machine.accumulator()));
+        assert!(disassemble(&program).contains("L3:     mul acc +2      ; 3\n        add a -1        ; 4\n        jnz a L3"));
+
+        let lines: Vec<String> = vec!["add acc +1".to_string(), "loop: mul acc +2".to_string(), "jnz acc loop".to_string()];
+        let program = assemble_with(&InstructionSet::extended(), &lines).unwrap();
+        let mut machine = Machine::new(&program);
+        assert_eq!(Termination::Overflow, machine.run_until(&[]));
+        assert_eq!((1, 1 << (isize::BITS - 2)), (machine.pc(), machine.accumulator()));
+
+        let double = InstructionKind {
+            name: "dbl",
+            register: true,
+            argument: Argument::None,
+            control: Control::Next,
+            execute: |registers, register, _| {
+                registers[register] *= 2;
+                Flow::Next
+            },
+        };
+        let lines: Vec<String> = vec!["acc +3".to_string(), "dbl acc".to_string()];
+        assert!(assemble_with(&InstructionSet::boot(), &lines).is_err());
+        let program = assemble_with(&InstructionSet::boot().with(double), &lines).unwrap();
+        let mut machine = Machine::new(&program);
+        assert_eq!((Termination::Exit, 6), (machine.run_until(&[]), machine.accumulator()));
+    }
+
+}
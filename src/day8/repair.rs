@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use super::{Instruction, InstructionKind, InstructionSet};
+use super::machine::{Machine, Termination};
+
+/// `instruction` with nop and jmp swapped, unless it's something else
+fn flip(instruction: Instruction) -> Option<Instruction> {
+    let kind = match instruction.kind.name {
+        "nop" => InstructionKind::JMP,
+        "jmp" => InstructionKind::NOP,
+        _ => return None,
+    };
+    Some(Instruction { kind, ..instruction })
+}
+
+/// The control-flow graph of a program, with one node per line plus one for the end, right after the last line
+pub struct Graph {
+    /// The lines that lead to each node
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    /// Edges that leave the program anywhere but at the end are dropped, since they can't lead to it
+    pub fn new(program: &[Instruction]) -> Graph {
+        let mut predecessors = vec![vec![]; program.len() + 1];
+        for (line, instruction) in program.iter().enumerate() {
+            for next in instruction.successors(line) {
+                if next >= 0 && next as usize <= program.len() {
+                    predecessors[next as usize].push(line);
+                }
+            }
+        }
+        Graph { predecessors }
+    }
+
+    fn end(&self) -> usize {
+        self.predecessors.len() - 1
+    }
+
+    /// Which nodes end up at the end, found by walking the edges backwards from it
+    pub fn reaches_end(&self) -> Vec<bool> {
+        let mut reaches = vec![false; self.predecessors.len()];
+        reaches[self.end()] = true;
+        let mut queue: VecDeque<usize> = vec![self.end()].into();
+        while let Some(node) = queue.pop_front() {
+            for &line in &self.predecessors[node] {
+                if !reaches[line] {
+                    reaches[line] = true;
+                    queue.push_back(line);
+                }
+            }
+        }
+        reaches
+    }
+}
+
+/// The instruction to flip to make a program terminate, and what it does then
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repair {
+    /// 0-based, like `target`
+    pub line: usize,
+    pub original: Instruction,
+    pub patched: Instruction,
+    /// Where the patched instruction leads, from which the unpatched program reaches the end
+    pub target: usize,
+    /// The accumulator once the patched program terminates
+    pub accumulator: isize,
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {} -> {}, which leads to line {}", self.line + 1, self.original, self.patched, self.target + 1)?;
+        write!(f, ", from where the program runs to the end with the accumulator at {}", self.accumulator)
+    }
+}
+
+/// Why a program can't be repaired
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unrepairable {
+    /// The (0-based) line isn't one of the instructions of `InstructionSet::boot`
+    Instruction(usize),
+    /// The program doesn't loop, so there is nothing to repair
+    NotLooping(Termination),
+    /// No nop or jmp can be flipped to make the program run to the end
+    NoFlip,
+    /// The patched program doesn't run to the end after all
+    Patched(Termination),
+}
+
+impl fmt::Display for Unrepairable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Unrepairable::Instruction(line) => write!(f, "line {}: only programs made of nop, acc and jmp can be repaired", line + 1),
+            Unrepairable::NotLooping(termination) => write!(f, "the program {} instead of looping", termination),
+            Unrepairable::NoFlip => write!(f, "no nop or jmp can be flipped to make the program run to the end"),
+            Unrepairable::Patched(termination) => write!(f, "the patched program {}", termination),
+        }
+    }
+}
+
+/// Find the nop or jmp to flip so that the program terminates, in time linear in its length.
+/// Only programs made of the instructions of `InstructionSet::boot` can be repaired.
+///
+/// Flipping a line the program never runs changes nothing, so only the lines it runs before looping are candidates.
+/// Flipping one of them works if it then leads to a line that reaches the end in the original program:
+/// that path can't go through the flipped line, since the original program would then terminate.
+pub fn repair(program: &[Instruction]) -> Result<Repair, Unrepairable> {
+    let boot = InstructionSet::boot();
+    if let Some(line) = program.iter().position(|instruction| boot.get(instruction.kind.name) != Some(instruction.kind)) {
+        return Err(Unrepairable::Instruction(line));
+    }
+    let reaches_end = Graph::new(program).reaches_end();
+    let mut machine = Machine::new(program);
+    machine.start_trace();
+    match machine.run_until(&[Termination::Loop]) {
+        Termination::Loop => {},
+        termination => return Err(Unrepairable::NotLooping(termination)),
+    }
+    let (line, original, patched, target) = machine
+    .trace()
+    .iter()
+    .filter_map(|step| Some((step.pc, step.instruction, flip(step.instruction)?)))
+    .map(|(line, original, patched)| (line, original, patched, patched.successors(line)[0]))
+    .find(|&(_, _, _, target)| target >= 0 && reaches_end.get(target as usize) == Some(&true))
+    .ok_or(Unrepairable::NoFlip)?;
+
+    let mut patched_program = program.to_vec();
+    patched_program[line] = patched;
+    let mut machine = Machine::new(&patched_program);
+    match machine.run_until(&[Termination::Loop]) {
+        Termination::Exit => Ok(Repair { line, original, patched, target: target as usize, accumulator: machine.accumulator() }),
+        termination => Err(Unrepairable::Patched(termination)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::INPUT;
+    use super::super::assembly::{assemble, assemble_with};
+
+    #[test]
+    fn repair_example() {
+        let program = assemble(&INPUT.lines().map(|s| s.to_string()).collect::<Vec<_>>()).unwrap();
+        let reaches_end = Graph::new(&program).reaches_end();
+        assert_eq!(vec![false, false, false, false, false, false, false, false, true, true], reaches_end);
+        let repair = repair(&program).unwrap();
+        assert_eq!((7, InstructionKind::NOP, 8, 8), (repair.line, repair.patched.kind, repair.target, repair.accumulator));
+        assert_eq!(
+            "line 8: jmp -4 -> nop -4, which leads to line 9, from where the program runs to the end with the accumulator at 8",
+            repair.to_string(),
+        );
+    }
+
+    #[test]
+    fn unrepairable() {
+        let program = |text: &str| assemble_with(&InstructionSet::extended(), &text.lines().map(|s| s.to_string()).collect::<Vec<_>>()).unwrap();
+        assert_eq!(Err(Unrepairable::NotLooping(Termination::Exit)), repair(&program("nop +0\nacc +1")));
+        assert_eq!(Err(Unrepairable::NotLooping(Termination::OutOfRange)), repair(&program("jmp +2")));
+        assert_eq!(Err(Unrepairable::Instruction(1)), repair(&program("acc +1\nhalt")));
+        assert_eq!(Err(Unrepairable::NoFlip), repair(&program("acc +1\njmp -1\njmp -1")));
+        assert_eq!("the program jumps outside of itself instead of looping", Unrepairable::NotLooping(Termination::OutOfRange).to_string());
+    }
+
+}
@@ -1,10 +1,53 @@
 use std::collections::VecDeque;
 
-use crate::lib;
+use crate::error::{Error, Result, parse_token};
+use crate::solver::{Answer, Example, Solver};
+
+pub struct Day9;
+
+impl Solver for Day9 {
+    type Input = Xmas;
+    const DAY: usize = 9;
+    const EXAMPLES: &'static [Example] = &[
+        Example { input: INPUT, answers: [Some("127"), Some("62")] },
+    ];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Xmas> {
+        let (preamble_length, header) = match lines.first().and_then(|line| line.strip_prefix("preamble:")) {
+            Some(length) => (parse_token(0, &lines[0], length.trim(), "a preamble length")?, 1),
+            None => (PREAMBLE_LENGTH, 0),
+        };
+        let numbers = lines
+        .iter()
+        .enumerate()
+        .skip(header)
+        .map(|(index, line)| parse_token(index, line, line, "a number"))
+        .collect::<Result<_>>()?;
+        Ok(Xmas { preamble_length, numbers })
+    }
+
+    fn part1(&self, xmas: &Xmas) -> Result<Answer> {
+        let (_, number) = find_first_wrong(xmas.numbers.iter().cloned(), xmas.preamble_length).ok_or(Error::NoSolution)?;
+        Ok(number.into())
+    }
+
+    fn part2(&self, xmas: &Xmas) -> Result<Answer> {
+        process2(xmas.numbers.clone(), xmas.preamble_length).map(Answer::from).ok_or(Error::NoSolution)
+    }
+}
 
-pub const INPUT_FILE: &str = "input/9.txt";
+/// Length of the preamble of the puzzle input
+pub const PREAMBLE_LENGTH: usize = 25;
 
-fn find_first_wrong(numbers: impl Iterator<Item=u64>, preamble_length: usize) -> Option<(usize, u64)> {
+/// The numbers sent by the port, along with how many of them make up the preamble.
+/// The input may start with a `preamble: <length>` line, for the example of the statement which uses 5.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xmas {
+    pub preamble_length: usize,
+    pub numbers: Vec<u64>,
+}
+
+pub fn find_first_wrong(numbers: impl Iterator<Item=u64>, preamble_length: usize) -> Option<(usize, u64)> {
 
     let mut preamble: VecDeque<u64> = VecDeque::new();
 
@@ -18,17 +61,15 @@ fn find_first_wrong(numbers: impl Iterator<Item=u64>, preamble_length: usize) ->
             return false;
         }
 
-        let mut i = 1;
         let slice = preamble.make_contiguous();
-        for first in &slice[..] {
-            for second in &slice[i..] {
+        for (i, first) in slice.iter().enumerate() {
+            for second in &slice[i + 1..] {
                 if first != second && first + second == number {
                     preamble.pop_front();
                     preamble.push_back(number);
                     return false;
                 }
             }
-            i += 1;
         }
 
         true
@@ -37,7 +78,7 @@ fn find_first_wrong(numbers: impl Iterator<Item=u64>, preamble_length: usize) ->
 
 }
 
-fn find_contiguous_summands(target: u64, numbers: Vec<u64>) -> Option<u64> {
+pub fn find_contiguous_summands(target: u64, numbers: Vec<u64>) -> Option<u64> {
     for start in 0..numbers.len() {
         for end in start..numbers.len() {
             let range = numbers[start..=end].to_vec();
@@ -49,7 +90,7 @@ fn find_contiguous_summands(target: u64, numbers: Vec<u64>) -> Option<u64> {
     None
 }
 
-fn process2(numbers: Vec<u64>, preamble_length: usize) -> Option<u64> {
+pub fn process2(numbers: Vec<u64>, preamble_length: usize) -> Option<u64> {
     let (index, number) = find_first_wrong(numbers.clone().into_iter(), preamble_length)?;
     let mut before = numbers.clone();
     let after = before.split_off(index);
@@ -57,23 +98,9 @@ fn process2(numbers: Vec<u64>, preamble_length: usize) -> Option<u64> {
     .or(find_contiguous_summands(number, after))
 }
 
-pub fn run1(file: &str) -> Option<u64> {
-    let numbers = lib::read_lines(file).ok()?.map(|s| s.parse().unwrap());
-    Some(find_first_wrong(numbers, 25)?.1)
-}
-
-pub fn run2(file: &str) -> Option<u64> {
-    let numbers: Vec<u64> = lib::read_lines(file).ok()?.map(|s| s.parse().unwrap()).collect();
-    process2(numbers, 25)
-}
-
-#[cfg(test)]
-mod test {
-    use super::{find_first_wrong, process2};
-
-
-    const INPUT: &str =
-"35
+const INPUT: &str =
+"preamble: 5
+35
 20
 15
 25
@@ -94,14 +121,30 @@ mod test {
 309
 576";
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn numbers() -> Vec<u64> {
+        INPUT.lines().skip(1).map(|s| s.parse().unwrap()).collect()
+    }
+
     #[test]
     fn run1() {
-        assert_eq!(127, find_first_wrong(INPUT.lines().map(|s| s.parse().unwrap()), 5).unwrap().1);
+        assert_eq!(127, find_first_wrong(numbers().into_iter(), 5).unwrap().1);
     }
 
     #[test]
     fn run2() {
-        assert_eq!(62, process2(INPUT.lines().map(|s| s.parse().unwrap()).collect(), 5).unwrap());
+        assert_eq!(62, process2(numbers(), 5).unwrap());
+    }
+
+    #[test]
+    fn preamble() {
+        let lines = |text: &str| text.lines().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(Xmas { preamble_length: 5, numbers: numbers() }, Day9.parse(lines(INPUT)).unwrap());
+        assert_eq!(PREAMBLE_LENGTH, Day9.parse(lines("1\n2")).unwrap().preamble_length);
+        assert!(Day9.parse(lines("preamble: x\n1")).is_err());
     }
 
 }
@@ -0,0 +1,150 @@
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+use crate::Source;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Everything that can go wrong while solving a puzzle
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// The input could not be read at all
+    Io {
+        file: String,
+        message: String,
+    },
+    /// A line of the input is malformed
+    Parse {
+        file: Option<String>,
+        /// 1-based
+        line: usize,
+        /// 1-based, in characters, when we know which part of the line is wrong
+        column: Option<usize>,
+        /// How many characters are wrong from the column on, at least 1
+        width: usize,
+        text: String,
+        message: String,
+    },
+    /// The input is malformed as a whole, e.g. it is missing a section
+    Input {
+        file: Option<String>,
+        message: String,
+    },
+    /// The input is well-formed, but there is no answer to it
+    NoSolution,
+}
+
+impl Error {
+    /// An error on the line with the given (0-based) index
+    pub fn parse(index: usize, text: &str, message: impl Into<String>) -> Error {
+        Error::Parse {
+            file: None,
+            line: index + 1,
+            column: None,
+            width: 1,
+            text: text.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// An error on `token`, which must be a slice of `text`, the line with the given (0-based) index
+    pub fn token(index: usize, text: &str, token: &str, message: impl Into<String>) -> Error {
+        Error::parse(index, text, message).at(column_of(text, token)).wide(token.chars().count())
+    }
+
+    pub fn input(message: impl Into<String>) -> Error {
+        Error::Input { file: None, message: message.into() }
+    }
+
+    pub fn io(source: &Source, error: io::Error) -> Error {
+        Error::Io { file: source.to_string(), message: error.to_string() }
+    }
+
+    /// Point the error at a (1-based) column of its line
+    pub fn at(self, at: Option<usize>) -> Error {
+        match self {
+            Error::Parse { file, line, text, message, .. } => Error::Parse { file, line, column: at, width: 1, text, message },
+            error => error,
+        }
+    }
+
+    /// Make the error about `width` characters from its column on
+    pub fn wide(self, width: usize) -> Error {
+        match self {
+            Error::Parse { file, line, column, text, message, .. } => {
+                Error::Parse { file, line, column, width: width.max(1), text, message }
+            },
+            error => error,
+        }
+    }
+
+    /// Attach the name of the input the error was found in
+    pub fn in_file(self, source: &Source) -> Error {
+        match self {
+            Error::Parse { line, column, width, text, message, .. } => {
+                Error::Parse { file: Some(source.to_string()), line, column, width, text, message }
+            },
+            Error::Input { message, .. } => Error::Input { file: Some(source.to_string()), message },
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { file, message } => write!(f, "{}: {}", file, message),
+            Error::Parse { file, line, column, width, text, message } => {
+                let file = file.as_deref().unwrap_or("<input>");
+                match column {
+                    Some(column) => writeln!(f, "{}:{}:{}: {}", file, line, column, message)?,
+                    None => writeln!(f, "{}:{}: {}", file, line, message)?,
+                }
+                let gutter = " ".repeat(line.to_string().len());
+                writeln!(f, "{} |", gutter)?;
+                write!(f, "{} | {}", line, text)?;
+                if let Some(column) = column {
+                    write!(f, "\n{} | {}{}", gutter, " ".repeat(column - 1), "^".repeat(*width))?;
+                }
+                Ok(())
+            },
+            Error::Input { file, message } => write!(f, "{}: {}", file.as_deref().unwrap_or("<input>"), message),
+            Error::NoSolution => write!(f, "no solution found"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// 1-based column in characters at which `token` starts, if it is a slice of `text`
+fn column_of(text: &str, token: &str) -> Option<usize> {
+    let start = text.as_ptr() as usize;
+    let offset = (token.as_ptr() as usize).checked_sub(start)?;
+    if offset > text.len() || !text.is_char_boundary(offset) {
+        return None;
+    }
+    Some(text[..offset].chars().count() + 1)
+}
+
+/// Parse `token`, a slice of the line `text` with the given (0-based) index, as a `what`
+pub fn parse_token<T: FromStr>(index: usize, text: &str, token: &str, what: &str) -> Result<T> {
+    token.parse().map_err(|_| Error::token(index, text, token, format!("expected {}, found {:?}", what, token)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn diagnostic() {
+        let line = "acc +1x";
+        let error = parse_token::<isize>(2, line, &line[4..], "a number").unwrap_err();
+        let expected = "input/8.txt:3:5: expected a number, found \"+1x\"
+  |
+3 | acc +1x
+  |     ^^^";
+        assert_eq!(expected, error.in_file(&Source::from_arg("input/8.txt")).to_string());
+    }
+
+}
@@ -0,0 +1,239 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::error::{Error, Result};
+
+/// Column and row of a cell, counting from the top left corner
+pub type Position = (usize, usize);
+
+/// How to move from a cell to another, as (columns, rows)
+pub type Offset = (isize, isize);
+
+/// Up, right, down and left
+pub const ORTHOGONAL: [Offset; 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// The eight directions, clockwise starting from up
+pub const DIRECTIONS: [Offset; 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+
+/// A rectangular grid of cells, stored row by row
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Panics if there aren't exactly `width * height` cells
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+        assert_eq!(width * height, cells.len(), "a {}x{} grid needs {} cells", width, height, width * height);
+        Grid { width, height, cells }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(Position) -> T) -> Grid<T> {
+        let cells = (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(&mut cell).collect();
+        Grid { width, height, cells }
+    }
+
+    /// One row per line and one cell per character, where `cell` returns None for the characters that aren't allowed.
+    /// All the rows must have the same width, and there must be at least one.
+    pub fn parse(lines: &[String], cell: impl Fn(char) -> Option<T>) -> Result<Grid<T>> {
+        let width = lines.first().ok_or_else(|| Error::input("the grid is empty"))?.chars().count();
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (index, line) in lines.iter().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                let value = cell(c)
+                .ok_or_else(|| Error::parse(index, line, format!("unexpected {:?} in the grid", c)).at(Some(column + 1)))?;
+                cells.push(value);
+            }
+            if line.chars().count() != width || width == 0 {
+                return Err(Error::parse(index, line, format!("expected a row of width {}", width)));
+            }
+        }
+        Ok(Grid::new(width, lines.len(), cells))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (x, y): Position) -> Option<&T> {
+        if x < self.width && y < self.height {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, (x, y): Position) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            Some(&mut self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// The grid repeats itself infinitely in every direction
+    pub fn get_wrapping(&self, (x, y): (isize, isize)) -> &T {
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+        &self.cells[y * self.width + x]
+    }
+
+    /// Where moving by `offset` from `position` leads, unless it falls outside the grid
+    pub fn offset(&self, (x, y): Position, (dx, dy): Offset) -> Option<Position> {
+        let x = (x as isize).checked_add(dx)?;
+        let y = (y as isize).checked_add(dy)?;
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Every position, row by row
+    pub fn positions(&self) -> impl Iterator<Item=Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell with its position, row by row
+    pub fn iter(&self) -> impl Iterator<Item=(Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item=&[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item=&T> {
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    /// The cells above, to the right, below and to the left that are inside the grid
+    pub fn neighbours4(&self, position: Position) -> impl Iterator<Item=Position> + '_ {
+        ORTHOGONAL.iter().filter_map(move |&offset| self.offset(position, offset))
+    }
+
+    /// The cells around, including the diagonals, that are inside the grid
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item=Position> + '_ {
+        DIRECTIONS.iter().filter_map(move |&offset| self.offset(position, offset))
+    }
+
+    /// The cells met when moving repeatedly by `offset` from `position` (excluded) until leaving the grid
+    pub fn ray(&self, position: Position, offset: Offset) -> impl Iterator<Item=Position> + '_ {
+        std::iter::successors(self.offset(position, offset), move |&position| self.offset(position, offset))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// One line per row, with a character per cell
+    pub fn render(&self, cell: impl Fn(&T) -> char) -> String {
+        self.rows().map(|row| row.iter().map(&cell).collect::<String>()).collect::<Vec<_>>().join("\n")
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Grid<T> {
+        Grid { width, height, cells: vec![value; width * height] }
+    }
+
+    /// A quarter turn clockwise
+    pub fn rotate(&self) -> Grid<T> {
+        Grid::from_fn(self.height, self.width, |(x, y)| self[(y, self.height - 1 - x)].clone())
+    }
+
+    /// Mirrored left to right
+    pub fn flip(&self) -> Grid<T> {
+        Grid::from_fn(self.width, self.height, |(x, y)| self[(self.width - 1 - x, y)].clone())
+    }
+
+    /// The eight ways of rotating and flipping the grid, starting with itself
+    pub fn orientations(&self) -> Vec<Grid<T>> {
+        let mut orientations = vec![];
+        let mut current = self.clone();
+        for _ in 0..4 {
+            let next = current.rotate();
+            let flipped = current.flip();
+            orientations.push(current);
+            orientations.push(flipped);
+            current = next;
+        }
+        orientations
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    /// Panics if the position is outside the grid
+    fn index(&self, position: Position) -> &T {
+        self.get(position).unwrap_or_else(|| panic!("{:?} is outside a {}x{} grid", position, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(position).unwrap_or_else(|| panic!("{:?} is outside a {}x{} grid", position, width, height))
+    }
+}
+
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(|&c| c))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grid(text: &str) -> Grid<char> {
+        let lines: Vec<String> = text.lines().map(|s| s.to_string()).collect();
+        Grid::parse(&lines, Some).unwrap()
+    }
+
+    #[test]
+    fn access() {
+        let grid = grid("abc\ndef");
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(Some(&'f'), grid.get((2, 1)));
+        assert_eq!(None, grid.get((3, 0)));
+        assert_eq!('c', *grid.get_wrapping((-1, 2)));
+        assert_eq!(vec![(1, 0), (2, 1), (0, 1)], grid.neighbours4((1, 1)).collect::<Vec<_>>());
+        assert_eq!(5, grid.neighbours8((1, 1)).count());
+        assert_eq!(vec![(1, 0), (2, 0)], grid.ray((0, 0), (1, 0)).collect::<Vec<_>>());
+        assert_eq!(vec!['c', 'f'], grid.column(2).cloned().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn transform() {
+        let grid = grid("abc\ndef");
+        assert_eq!("da\neb\nfc", grid.rotate().to_string());
+        assert_eq!("cba\nfed", grid.flip().to_string());
+        assert_eq!(grid, grid.rotate().rotate().rotate().rotate());
+        let orientations = grid.orientations();
+        assert_eq!(8, orientations.len());
+        assert!(orientations.iter().all(|other| orientations.iter().filter(|&o| o == other).count() == 1));
+    }
+
+    #[test]
+    fn malformed() {
+        let lines: Vec<String> = vec!["..#".to_string(), ".x.".to_string()];
+        assert!(Grid::parse(&lines, |c| Some(c).filter(|&c| c == '.' || c == '#')).is_err());
+        assert!(Grid::parse(&lines[..1], |c| Some(c).filter(|&c| c == '.' || c == '#')).is_ok());
+        assert!(Grid::<char>::parse(&[], Some).is_err());
+    }
+
+}
@@ -1,7 +1,82 @@
+#[macro_use] extern crate lazy_static;
+
+pub mod answers;
+pub mod automaton;
+pub mod bench;
+pub mod blocks;
+pub mod error;
+pub mod grid;
+pub mod runner;
+pub mod solver;
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+
+use std::fmt;
 use std::fs::File;
 use std::io::{self, BufRead};
+use std::path::PathBuf;
+
+pub use blocks::{read_blocks, read_sections};
+pub use grid::Grid;
+
+/// Where a puzzle input comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    File(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    /// `-` stands for the standard input, anything else is a path
+    pub fn from_arg(arg: &str) -> Source {
+        match arg {
+            "-" => Source::Stdin,
+            path => Source::File(PathBuf::from(path)),
+        }
+    }
+
+    /// The usual `<dir>/<day>.txt`
+    pub fn in_dir(dir: &str, day: usize) -> Source {
+        Source::File(PathBuf::from(dir).join(format!("{}.txt", day)))
+    }
+
+    pub fn read_lines(&self) -> io::Result<Vec<String>> {
+        match self {
+            Source::File(path) => io::BufReader::new(File::open(path)?).lines().collect(),
+            Source::Stdin => io::stdin().lock().lines().collect(),
+        }
+    }
+}
 
-pub fn read_lines(file: &str) -> io::Result<impl Iterator<Item=String>> {
-    let file = File::open(file)?;
-    Ok(io::BufReader::new(file).lines().into_iter().flatten())
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Stdin => write!(f, "<stdin>"),
+        }
+    }
 }
@@ -1,88 +1,655 @@
-#[macro_use] extern crate lazy_static;
-
-mod lib;
-
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-
 use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::process;
+
+use aoc2020::answers::{self, Answers, Status};
+use aoc2020::error::Error;
+use aoc2020::solver::{self, Example, Puzzle, Solver};
+use aoc2020::day18::repl::{self, Repl};
+use aoc2020::{bench, day17, day18, runner, Source};
+
+const USAGE: &str = "Usage:
+    aoc2020 [options] <day> [part]    solve one or both parts of a day
+    aoc2020 [options] all             solve every part of every day, with timings
+    aoc2020 [options] <from>..<to>    same as all, for a range of days (also ..=, <from>.. and ..<to>)
+    aoc2020 [options] verify [days]   check the answers against the answers file, for all days or a day or range of days
+    aoc2020 [options] bench [days]    time repeated runs of each part, for all days or a day or range of days
+    aoc2020 list                      list the available days and parts
+    aoc2020 repl                      evaluate day 18 expressions typed line by line, :help for the commands
+
+Options:
+    --input <file>        read the input of a single day from <file>, or from the standard input if <file> is -,
+                          also when benching a single day
+    --example             solve the examples from the puzzle statements instead of the inputs, and check their answers
+    --format <text|json>  print the answers as text (default) or as JSON with their status, errors and timings
+    --input-dir <dir>     read the input of day N from <dir>/N.txt (default: input)
+    --user <name>         same as --input-dir inputs/<name>
+    --answers <file>      answers file used by verify (default: answers.toml)
+    --record              make verify write the current answers into the answers file instead of checking them
+    --warmup <n>          untimed runs before bench starts measuring (default: 3)
+    --runs <n>            timed runs of each part in bench (default: 10)
+    --save <file>         save the median times measured by bench as a baseline
+    --baseline <file>     compare the times measured by bench with a saved baseline
+    --threshold <pct>     how much slower than the baseline a part must be to be flagged (default: 10)
+    --dimensions <n>      for day 17 without a part, count the active cubes in <n> dimensions, from 3 to 6
+    --symmetric           with --dimensions, only simulate one of each set of cubes that are mirror images of each other
+    --render <cycles>     for day 17 without a part, print every layer before and after each cycle up to <cycles>,
+                          in 3 dimensions or as many as --dimensions says
+    --skip-invalid        for day 18, leave out the lines that can't be evaluated and list them, instead of failing
+    --bytecode            evaluate day 18 by compiling it to bytecode for a stack machine, e.g. to compare timings with
+                          bench 18, on the puzzle input or on a larger file given with --input
+    --dump-bytecode       for day 18, print the bytecode of the input for each part instead of solving it";
+
+const DEFAULT_INPUT_DIR: &str = "input";
+
+#[derive(Debug, PartialEq)]
+enum Format {
+    Text,
+    Json,
+}
+
+struct Options {
+    /// Overrides the input of the (single) day being solved
+    input: Option<Source>,
+    input_dir: String,
+    /// Solve the registered examples instead of the input
+    example: bool,
+    format: Format,
+    answers: String,
+    /// Whether verify records the answers instead of checking them
+    record: bool,
+    warmup: usize,
+    runs: usize,
+    /// Where bench saves its baseline
+    save: Option<String>,
+    /// What bench compares against
+    baseline: Option<String>,
+    threshold: f64,
+    /// Replaces the parts of day 17 with a simulation in this many dimensions
+    dimensions: Option<usize>,
+    /// Whether day 17 exploits the symmetry of the pocket dimension
+    symmetric: bool,
+    /// Replaces the parts of day 17 with the states up to this cycle
+    render: Option<usize>,
+    /// Whether day 18 evaluates the lines it can instead of failing
+    skip_invalid: bool,
+    /// Whether day 18 runs on the stack machine instead of walking the expressions
+    bytecode: bool,
+    /// Replaces the parts of day 18 with their bytecode
+    dump_bytecode: bool,
+}
+
+impl Options {
+    fn source(&self, day: usize) -> Source {
+        self.input.clone().unwrap_or_else(|| Source::in_dir(&self.input_dir, day))
+    }
+}
+
+/// Split the arguments into options and positional arguments
+fn parse_args(args: &[String]) -> Result<(Options, Vec<String>), String> {
+    let mut options = Options {
+        input: None,
+        input_dir: DEFAULT_INPUT_DIR.to_string(),
+        example: false,
+        format: Format::Text,
+        answers: answers::DEFAULT_ANSWERS_FILE.to_string(),
+        record: false,
+        warmup: bench::DEFAULT_WARMUP,
+        runs: bench::DEFAULT_RUNS,
+        save: None,
+        baseline: None,
+        threshold: bench::DEFAULT_THRESHOLD,
+        dimensions: None,
+        symmetric: false,
+        render: None,
+        skip_invalid: false,
+        bytecode: false,
+        dump_bytecode: false,
+    };
+    let mut positional = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--input" => options.input = Some(Source::from_arg(&value()?)),
+            "--input-dir" => options.input_dir = value()?,
+            "--user" => options.input_dir = format!("inputs/{}", value()?),
+            "--example" => options.example = true,
+            "--format" => options.format = match value()?.as_str() {
+                "text" => Format::Text,
+                "json" => Format::Json,
+                format => return Err(format!("Unknown format {:?}, expected text or json", format)),
+            },
+            "--answers" => options.answers = value()?,
+            "--record" => options.record = true,
+            "--warmup" => options.warmup = number(arg, &value()?)?,
+            "--runs" => options.runs = number(arg, &value()?)?,
+            "--save" => options.save = Some(value()?),
+            "--baseline" => options.baseline = Some(value()?),
+            "--threshold" => options.threshold = number(arg, &value()?)?,
+            "--dimensions" => options.dimensions = Some(number(arg, &value()?)?),
+            "--symmetric" => options.symmetric = true,
+            "--render" => options.render = Some(number(arg, &value()?)?),
+            "--skip-invalid" => options.skip_invalid = true,
+            "--bytecode" => options.bytecode = true,
+            "--dump-bytecode" => options.dump_bytecode = true,
+            flag if flag.starts_with('-') => return Err(format!("Unknown option {}\n\n{}", flag, USAGE)),
+            _ => positional.push(arg.clone()),
+        }
+    }
+    Ok((options, positional))
+}
+
+fn number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid value {:?} for {}", value, option))
+}
+
+/// The registered puzzles, with the bytecode version of day 18 if --bytecode says so
+fn registry(options: &Options) -> Vec<Box<dyn Puzzle>> {
+    solver::registry()
+    .into_iter()
+    .map(|puzzle| match puzzle.day() {
+        18 if options.bytecode => Box::new(day18::bytecode::Bytecode),
+        _ => puzzle,
+    })
+    .collect()
+}
+
+fn list() {
+    for puzzle in solver::registry() {
+        let parts: Vec<String> = (1..=puzzle.parts()).map(|part| part.to_string()).collect();
+        println!("day {:>2}: parts {}", puzzle.day(), parts.join(", "));
+    }
+}
+
+/// Solve the examples of each puzzle, for the given parts, and compare the answers with the expected ones
+fn run_examples(puzzles: &[Box<dyn Puzzle>], parts: &[usize]) -> Result<(), String> {
+    let mut failed = false;
+    for puzzle in puzzles {
+        if puzzle.examples().is_empty() {
+            println!("day {:>2}  no examples", puzzle.day());
+        }
+        for (i, example) in puzzle.examples().iter().enumerate() {
+            let input = puzzle.parse(example.lines());
+            for &part in parts.iter().filter(|&&part| part <= puzzle.parts()) {
+                let expected = match example.expected(part) {
+                    Some(expected) => expected,
+                    None => continue,
+                };
+                let label = format!("day {:>2} example {} part {}", puzzle.day(), i + 1, part);
+                match input.as_ref().map_err(Error::clone).and_then(|input| puzzle.solve(part, input.as_ref())) {
+                    Ok(answer) if answer.to_string() == expected => println!("{}  ok    {}", label, answer),
+                    Ok(answer) => {
+                        println!("{}  FAIL  expected {}, got {}", label, expected, answer);
+                        failed = true;
+                    },
+                    Err(error) => {
+                        println!("{}  error expected {}\n{}", label, expected, error);
+                        failed = true;
+                    },
+                }
+            }
+        }
+    }
+    if failed {
+        Err("Some examples failed".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Solve the given parts of a day, printing one answer per line, or a JSON report
+fn solve(puzzle: &dyn Puzzle, parts: &[usize], source: Source, format: &Format) -> Result<(), String> {
+    if *format == Format::Json {
+        let reports = runner::time_day(puzzle, parts, &source);
+        runner::print_json(&reports);
+        let failed = reports.iter().any(|report| report.answer.is_err());
+        return if failed { Err("Some parts could not be solved".to_string()) } else { Ok(()) };
+    }
+
+    let diagnostic = |error: Error| error.in_file(&source).to_string();
+    let lines = source.read_lines().map_err(|error| diagnostic(Error::io(&source, error)))?;
+    let input = puzzle.parse(lines).map_err(diagnostic)?;
+    for &part in parts {
+        let answer = puzzle
+        .solve(part, input.as_ref())
+        .map_err(|error| format!("day {} part {}: {}", puzzle.day(), part, diagnostic(error)))?;
+        println!("{}", answer);
+        if let Some(note) = puzzle.note(part, input.as_ref()) {
+            eprintln!("note: {}", note);
+        }
+    }
+    Ok(())
+}
+
+/// The lines of `example` with --example, or else those of `source`
+fn read_input(source: &Source, example: &Example, options: &Options) -> Result<Vec<String>, String> {
+    if options.example {
+        Ok(example.lines())
+    } else {
+        source.read_lines().map_err(|error| Error::io(source, error).in_file(source).to_string())
+    }
+}
+
+/// Count the active cubes of day 17 after the boot process in the given number of dimensions,
+/// or print the states up to the cycle given by --render
+fn simulate(dimensions: usize, options: &Options) -> Result<(), String> {
+    let unsupported = || format!("--dimensions must be between {} and {}", day17::DIMENSIONS.start(), day17::DIMENSIONS.end());
+    if !day17::DIMENSIONS.contains(&dimensions) {
+        return Err(unsupported());
+    }
+    if options.format == Format::Json {
+        return Err("--format json can't be used with --dimensions or --render".to_string());
+    }
+    if options.symmetric && options.render.is_some() {
+        return Err("--symmetric can't be used with --render".to_string());
+    }
+
+    let source = options.source(17);
+    let diagnostic = |error: Error| error.in_file(&source).to_string();
+    let lines = read_input(&source, &day17::Day17::EXAMPLES[0], options)?;
+    let slice = day17::parse(&lines).map_err(diagnostic)?;
+    match options.render {
+        Some(cycles) => print!("{}", day17::render_cycles(&slice, dimensions, cycles).ok_or_else(unsupported)?),
+        None => println!("{}", day17::simulate(&slice, dimensions, day17::CYCLES, options.symmetric).ok_or_else(unsupported)?),
+    }
+    Ok(())
+}
+
+/// Solve the given parts of day 18 with the lines that can be evaluated,
+/// after listing the others on the standard error
+fn skip_invalid(parts: &[usize], options: &Options) -> Result<(), String> {
+    if options.format == Format::Json || options.bytecode {
+        return Err("--skip-invalid can't be used with --format json or --bytecode".to_string());
+    }
+
+    let source = options.source(18);
+    let diagnostic = |error: Error| error.in_file(&source).to_string();
+    let lines = read_input(&source, &day18::Day18::EXAMPLES[0], options)?;
+    let results: Vec<_> = parts.iter().map(|&part| day18::run_lenient(&lines, &day18::PRECEDENCES[part - 1])).collect();
+    // The same line is usually invalid in both parts
+    let mut invalid: Vec<String> = vec![];
+    for error in results.iter().flat_map(|(_, errors)| errors) {
+        let message = diagnostic(error.clone());
+        if !invalid.contains(&message) {
+            invalid.push(message);
+        }
+    }
+    for message in &invalid {
+        eprintln!("{}\n", message);
+    }
+    if !invalid.is_empty() {
+        eprintln!("{} invalid line(s) left out", invalid.len());
+    }
+    for (sum, _) in results {
+        println!("{}", sum);
+    }
+    Ok(())
+}
+
+/// Print the day 18 bytecode of the given parts
+fn dump_bytecode(parts: &[usize], options: &Options) -> Result<(), String> {
+    if options.format == Format::Json || options.skip_invalid {
+        return Err("--dump-bytecode can't be used with --format json or --skip-invalid".to_string());
+    }
+
+    let source = options.source(18);
+    let diagnostic = |error: Error| error.in_file(&source).to_string();
+    let lines = read_input(&source, &day18::Day18::EXAMPLES[0], options)?;
+    for &part in parts {
+        let program = day18::bytecode::compile_lines(&lines, &day18::PRECEDENCES[part - 1]).map_err(diagnostic)?;
+        println!("; part {}", part);
+        print!("{}", program);
+    }
+    Ok(())
+}
+
+/// Parse a range of days in Rust syntax: `5..12` (12 excluded), `5..=12`, `5..` or `..12`.
+/// The range must not be empty, and only hold days from 1 to 25.
+fn parse_range(range: &str) -> Result<RangeInclusive<usize>, Error> {
+    let invalid = || Error::Input {
+        file: Some(range.to_string()),
+        message: "expected a day or a range of days like 5..12, 5..=12, 5.. or ..12".to_string(),
+    };
+    let (from, to) = range.split_at(range.find("..").ok_or_else(invalid)?);
+    let from = if from.is_empty() { 1 } else { from.parse().map_err(|_| invalid())? };
+    let to = if let Some(to) = to.strip_prefix("..=") {
+        to.parse().map_err(|_| invalid())?
+    } else {
+        match &to[2..] {
+            "" => 25,
+            // `..1` ends up empty
+            to => to.parse::<usize>().map_err(|_| invalid())?.saturating_sub(1),
+        }
+    };
+    check_days(range, from..=to)
+}
+
+/// Reject an empty range of days, or one with days outside the calendar, `text` being how it was written
+fn check_days(text: &str, days: RangeInclusive<usize>) -> Result<RangeInclusive<usize>, Error> {
+    let invalid = |message: &str| Error::Input { file: Some(text.to_string()), message: message.to_string() };
+    if days.is_empty() {
+        Err(invalid("the range of days is empty"))
+    } else if *days.start() < 1 || *days.end() > 25 {
+        Err(invalid("days go from 1 to 25"))
+    } else {
+        Ok(days)
+    }
+}
+
+/// Solve every part of every registered day in `days`, then print a table with the answers and timings
+fn run_all(days: RangeInclusive<usize>, options: &Options) -> Result<(), String> {
+    if options.input.is_some() {
+        return Err("--input can only be used to solve a single day".to_string());
+    }
+    if options.example {
+        let puzzles: Vec<Box<dyn Puzzle>> = registry(options).into_iter().filter(|puzzle| days.contains(&puzzle.day())).collect();
+        return run_examples(&puzzles, &[1, 2]);
+    }
+
+    let mut reports = vec![];
+    for puzzle in registry(options).into_iter().filter(|puzzle| days.contains(&puzzle.day())) {
+        let parts: Vec<usize> = (1..=puzzle.parts()).collect();
+        reports.extend(runner::time_day(puzzle.as_ref(), &parts, &options.source(puzzle.day())));
+    }
+    let failed = reports.iter().any(|report| report.answer.is_err());
+
+    if options.format == Format::Json {
+        runner::print_json(&reports);
+        return if failed { Err("Some parts could not be solved".to_string()) } else { Ok(()) };
+    }
+
+    runner::print_table(&reports);
+
+    // Parsing errors show up once per part, but are only worth printing once
+    let mut printed: Option<(usize, String)> = None;
+    for report in &reports {
+        if let Err(error) = &report.answer {
+            let diagnostic = Some((report.day, error.to_string()));
+            if diagnostic != printed {
+                eprintln!("\nday {} part {}: {}", report.day, report.part, error);
+                printed = diagnostic;
+            }
+        }
+    }
+
+    if failed {
+        Err("Some parts could not be solved".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Solve every part of every registered day in `days` and compare the answers with the answers file,
+/// or write them into it if recording
+fn verify(days: RangeInclusive<usize>, options: &Options) -> Result<(), String> {
+    if options.input.is_some() {
+        return Err("--input can't be used with verify".to_string());
+    }
+    if options.format == Format::Json {
+        return Err("--format json can't be used with verify".to_string());
+    }
+
+    let file = Source::File(PathBuf::from(&options.answers));
+    let mut answers = match file.read_lines() {
+        Ok(lines) => answers::parse(&lines).map_err(|error| error.in_file(&file).to_string())?,
+        // Recording from scratch
+        Err(error) if options.record && error.kind() == io::ErrorKind::NotFound => Answers::new(),
+        Err(error) => return Err(Error::io(&file, error).to_string()),
+    };
+
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    for puzzle in registry(options).into_iter().filter(|puzzle| days.contains(&puzzle.day())) {
+        let day = puzzle.day();
+        let source = options.source(day);
+        let lines = match source.read_lines() {
+            Ok(lines) => lines,
+            Err(error) => {
+                println!("day {:>2}         error\n{}", day, Error::io(&source, error));
+                failed += puzzle.parts();
+                continue;
+            },
+        };
+        for part in 1..=puzzle.parts() {
+            let answer = match runner::time_part(puzzle.as_ref(), part, lines.clone()).answer {
+                Ok(answer) => answer,
+                Err(error) => {
+                    println!("day {:>2} part {}  error\n{}", day, part, error.in_file(&source));
+                    failed += 1;
+                    continue;
+                },
+            };
+            if options.record {
+                println!("day {:>2} part {}  recorded  {}", day, part, answer);
+                answers.insert((day, part), answer);
+                passed += 1;
+                continue;
+            }
+            match answers::check(&answers, day, part, &answer) {
+                Status::Pass => {
+                    println!("day {:>2} part {}  pass", day, part);
+                    passed += 1;
+                },
+                Status::Fail { expected } => {
+                    println!("day {:>2} part {}  FAIL\n    - {}\n    + {}", day, part, expected, answer);
+                    failed += 1;
+                },
+                Status::Missing => {
+                    println!("day {:>2} part {}  missing   (got {})", day, part, answer);
+                    missing += 1;
+                },
+            }
+        }
+    }
+
+    if options.record {
+        fs::write(&options.answers, answers::render(&answers)).map_err(|error| format!("{}: {}", file, error))?;
+        println!("{} answers recorded into {}, {} errors", passed, file, failed);
+    } else {
+        println!("{} passed, {} failed, {} missing", passed, failed, missing);
+    }
+
+    if failed > 0 {
+        Err("Some answers could not be verified".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Time repeated runs of every part of every registered day in `days`,
+/// optionally comparing them with a baseline and saving them as the new one
+fn run_bench(days: RangeInclusive<usize>, options: &Options) -> Result<(), String> {
+    if options.input.is_some() && days.start() != days.end() {
+        return Err("--input can only be used with bench for a single day".to_string());
+    }
+    if options.format == Format::Json {
+        return Err("--format json can't be used with bench".to_string());
+    }
+    if options.runs == 0 {
+        return Err("--runs must be at least 1".to_string());
+    }
+
+    let baseline = match &options.baseline {
+        Some(path) => {
+            let file = Source::File(PathBuf::from(path));
+            let lines = file.read_lines().map_err(|error| Error::io(&file, error).to_string())?;
+            Some(bench::parse_baseline(&lines).map_err(|error| error.in_file(&file).to_string())?)
+        },
+        None => None,
+    };
+
+    let mut measurements = vec![];
+    let mut failed = false;
+    for puzzle in registry(options).into_iter().filter(|puzzle| days.contains(&puzzle.day())) {
+        let source = options.source(puzzle.day());
+        let lines = match source.read_lines() {
+            Ok(lines) => lines,
+            Err(error) => {
+                eprintln!("{}", Error::io(&source, error));
+                failed = true;
+                continue;
+            },
+        };
+        for part in 1..=puzzle.parts() {
+            match bench::measure(puzzle.as_ref(), part, &lines, options.warmup, options.runs) {
+                Ok(measurement) => measurements.push(measurement),
+                Err(error) => {
+                    eprintln!("day {} part {}: {}", puzzle.day(), part, error.in_file(&source));
+                    failed = true;
+                },
+            }
+        }
+    }
+
+    let slower = bench::print_table(&measurements, baseline.as_ref(), options.threshold);
+
+    if let Some(path) = &options.save {
+        fs::write(path, bench::render_baseline(&measurements)).map_err(|error| format!("{}: {}", path, error))?;
+    }
+
+    if failed {
+        Err("Some parts could not be solved".to_string())
+    } else if slower > 0 {
+        Err(format!("{} parts are more than {}% slower than the baseline", slower, options.threshold))
+    } else {
+        Ok(())
+    }
+}
+
+/// Evaluate the day 18 expressions typed on the standard input until it ends or the user quits
+fn run_repl() -> Result<(), String> {
+    let mut repl = Repl::new();
+    let stdin = io::stdin();
+    loop {
+        print!("{}", repl::PROMPT);
+        io::stdout().flush().map_err(|error| error.to_string())?;
+        let mut line = String::new();
+        if stdin.read_line(&mut line).map_err(|error| error.to_string())? == 0 {
+            println!();
+            return Ok(());
+        }
+        match repl.input(&line) {
+            Some(reply) if reply.is_empty() => {},
+            Some(reply) => println!("{}", reply),
+            None => return Ok(()),
+        }
+    }
+}
+
+/// A range of days like the ones accepted by `parse_range`, or a single day
+fn parse_days(days: &str) -> Result<RangeInclusive<usize>, Error> {
+    match days.parse() {
+        Ok(day) => check_days(days, day..=day),
+        Err(_) => parse_range(days),
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (options, args) = parse_args(args)?;
+    if options.symmetric && options.dimensions.is_none() {
+        return Err("--symmetric can only be used with --dimensions".to_string());
+    }
+    if options.example && options.format == Format::Json {
+        return Err("--format json can't be used with --example".to_string());
+    }
+    match args.as_slice() {
+        [day, ..] if (options.skip_invalid || options.dump_bytecode) && day.parse() != Ok(18) => {
+            Err("--skip-invalid and --dump-bytecode can only be used with day 18".to_string())
+        },
+        [command] if command == "list" => {
+            list();
+            Ok(())
+        },
+        [command] if command == "repl" => run_repl(),
+        [command] if command == "all" => run_all(1..=25, &options),
+        [command] if command == "verify" => verify(1..=25, &options),
+        [command] if command == "bench" => run_bench(1..=25, &options),
+        [command, days] if command == "bench" => {
+            let days = parse_days(days).map_err(|error| error.to_string())?;
+            run_bench(days, &options)
+        },
+        [command, days] if command == "verify" => {
+            let days = parse_days(days).map_err(|error| error.to_string())?;
+            verify(days, &options)
+        },
+        [range] if range.contains("..") => {
+            let days = parse_range(range).map_err(|error| error.to_string())?;
+            run_all(days, &options)
+        },
+        [day] if options.dimensions.is_some() || options.render.is_some() => match day.parse() {
+            Ok(17) => simulate(options.dimensions.unwrap_or(3), &options),
+            _ => Err("--dimensions and --render can only be used with day 17".to_string()),
+        },
+        _ if options.dimensions.is_some() || options.render.is_some() => {
+            Err("--dimensions and --render can only be used with day 17, without a part".to_string())
+        },
+        [day] | [day, _] => {
+            let day: usize = day.parse().map_err(|_| format!("Unknown day {:?}", day))?;
+            let puzzle = registry(&options)
+            .into_iter()
+            .find(|puzzle| puzzle.day() == day)
+            .ok_or(format!("Unknown day {:?}", day))?;
+            let parts: Vec<usize> = match args.get(1) {
+                Some(part) => match part.parse() {
+                    Ok(part) if part >= 1 && part <= puzzle.parts() => vec![part],
+                    _ => return Err(format!("Unknown problem {:?} {:?}", day, part)),
+                },
+                None => (1..=puzzle.parts()).collect(),
+            };
+            if options.dump_bytecode {
+                dump_bytecode(&parts, &options)
+            } else if options.skip_invalid {
+                skip_invalid(&parts, &options)
+            } else if options.example {
+                run_examples(&[puzzle], &parts)
+            } else {
+                solve(puzzle.as_ref(), &parts, options.source(day), &options.format)
+            }
+        },
+        _ => Err(USAGE.to_string()),
+    }
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-
-    match (args[1].as_str(), args[2].as_str()) {
-        ("1", "1") => { println!("{}", day1::run1().unwrap()); }
-        ("1", "2") => { println!("{}", day1::run2().unwrap()); }
-        ("2", "1") => { println!("{}", day2::run1().unwrap()); }
-        ("2", "2") => { println!("{}", day2::run2().unwrap()); }
-        ("3", "1") => { println!("{}", day3::run1(day3::INPUT_FILE).unwrap()); }
-        ("3", "2") => { println!("{}", day3::run2(day3::INPUT_FILE).unwrap()); }
-        ("4", "1") => { println!("{}", day4::run1(day4::INPUT_FILE).unwrap()); }
-        ("4", "2") => { println!("{}", day4::run2(day4::INPUT_FILE).unwrap()); }
-        ("5", "1") => { println!("{}", day5::run1(day5::INPUT_FILE).unwrap()); }
-        ("5", "2") => { println!("{}", day5::run2(day5::INPUT_FILE).unwrap()); }
-        ("6", "1") => { println!("{}", day6::run1(day6::INPUT_FILE).unwrap()); }
-        ("6", "2") => { println!("{}", day6::run2(day6::INPUT_FILE).unwrap()); }
-        ("7", "1") => { println!("{}", day7::run1(day7::INPUT_FILE).unwrap()); }
-        ("7", "2") => { println!("{}", day7::run2(day7::INPUT_FILE).unwrap()); }
-        ("8", "1") => { println!("{}", day8::run1(day8::INPUT_FILE).unwrap()); }
-        ("8", "2") => { println!("{}", day8::run2(day8::INPUT_FILE).unwrap()); }
-        ("9", "1") => { println!("{}", day9::run1(day9::INPUT_FILE).unwrap()); }
-        ("9", "2") => { println!("{}", day9::run2(day9::INPUT_FILE).unwrap()); }
-        ("10", "1") => { println!("{}", day10::run1(day10::INPUT_FILE).unwrap()); }
-        ("10", "2") => { println!("{}", day10::run2(day10::INPUT_FILE).unwrap()); }
-        ("11", "1") => { println!("{}", day11::run1(day11::INPUT_FILE).unwrap()); }
-        ("11", "2") => { println!("{}", day11::run2(day11::INPUT_FILE).unwrap()); }
-        ("12", "1") => { println!("{}", day12::part1::run(day12::INPUT_FILE).unwrap()); }
-        ("12", "2") => { println!("{}", day12::part2::run(day12::INPUT_FILE).unwrap()); }
-        ("13", "1") => { println!("{}", day13::run1(day13::INPUT_FILE).unwrap()); }
-        ("13", "2") => { println!("{}", day13::run2(day13::INPUT_FILE).unwrap()); }
-        ("14", "1") => { println!("{}", day14::run1(day14::INPUT_FILE).unwrap()); }
-        ("14", "2") => { println!("{}", day14::run2(day14::INPUT_FILE).unwrap()); }
-        ("15", "1") => { println!("{}", day15::run1(day15::INPUT_FILE).unwrap()); }
-        ("15", "2") => { println!("{}", day15::run2(day15::INPUT_FILE).unwrap()); }
-        ("16", "1") => { println!("{}", day16::run1(day16::INPUT_FILE).unwrap()); }
-        ("16", "2") => { println!("{}", day16::run2(day16::INPUT_FILE).unwrap()); }
-        ("17", "1") => { println!("{}", day17::run1(day17::INPUT_FILE).unwrap()); }
-        ("17", "2") => { println!("{}", day17::run2(day17::INPUT_FILE).unwrap()); }
-        ("18", "1") => { println!("{}", day18::run1(day18::INPUT_FILE).unwrap()); }
-        ("18", "2") => { println!("{}", day18::run2(day18::INPUT_FILE).unwrap()); }
-        ("19", "1") => { println!("{}", day19::run1(day19::INPUT_FILE).unwrap()); }
-        ("19", "2") => { println!("{}", day19::run2(day19::INPUT_FILE).unwrap()); }
-        ("20", "1") => { println!("{}", day20::run1(day20::INPUT_FILE).unwrap()); }
-        // ("20", "2") => { println!("{}", day20::run2(day20::INPUT_FILE).unwrap()); }
-        ("21", "1") => { println!("{}", day21::run1(day21::INPUT_FILE).unwrap()); }
-        ("21", "2") => { println!("{}", day21::run2(day21::INPUT_FILE).unwrap()); }
-        ("22", "1") => { println!("{}", day22::part1::run(day22::INPUT_FILE).unwrap()); }
-        ("22", "2") => { println!("{}", day22::part2::run(day22::INPUT_FILE).unwrap()); }
-        ("23", "1") => { println!("{}", day23::run1(day23::INPUT_FILE).unwrap()); }
-        ("23", "2") => { println!("{}", day23::run2(day23::INPUT_FILE).unwrap()); }
-        ("24", "1") => { println!("{}", day24::run1(day24::INPUT_FILE).unwrap()); }
-        ("24", "2") => { println!("{}", day24::run2(day24::INPUT_FILE).unwrap()); }
-        ("25", "1") => { println!("{}", day25::run1(day25::INPUT_FILE).unwrap()); }
-        _ => { panic!("Unknown problem {:?} {:?}", &args[1], &args[2]) }
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Err(message) = run(&args) {
+        eprintln!("{}", message);
+        process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Source, parse_args, parse_days, parse_range};
+
+    #[test]
+    fn ranges() {
+        assert_eq!(5..=11, parse_range("5..12").unwrap());
+        assert_eq!(5..=12, parse_range("5..=12").unwrap());
+        assert_eq!(20..=25, parse_range("20..").unwrap());
+        assert_eq!(1..=4, parse_range("..5").unwrap());
+        assert_eq!(7..=7, parse_days("7").unwrap());
+        assert_eq!(5..=11, parse_days("5..12").unwrap());
+        assert_eq!("5..x: expected a day or a range of days like 5..12, 5..=12, 5.. or ..12", parse_range("5..x").unwrap_err().to_string());
+        assert_eq!("5..5: the range of days is empty", parse_range("5..5").unwrap_err().to_string());
+        for days in &["7..3", "..1", "0..3", "20..40", "26..", "0", "26"] {
+            assert!(parse_days(days).is_err(), "{}", days);
+        }
+    }
+
+    #[test]
+    fn options() {
+        let args: Vec<String> = vec!["--user", "alice", "5", "--input", "-", "2"].into_iter().map(|s| s.to_string()).collect();
+        let (options, positional) = parse_args(&args).unwrap();
+        assert_eq!(vec!["5", "2"], positional);
+        assert_eq!("inputs/alice", options.input_dir);
+        assert_eq!(Source::Stdin, options.source(5));
+        assert!(!options.record);
+        assert!(parse_args(&["--input".to_string()]).is_err());
+    }
+
+}
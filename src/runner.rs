@@ -0,0 +1,147 @@
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+use crate::Source;
+use crate::solver::{Answer, Puzzle};
+
+/// Outcome of solving one part of a day, with how long each phase took
+pub struct Report {
+    pub day: usize,
+    pub part: usize,
+    pub answer: Result<Answer>,
+    pub parse: Duration,
+    pub solve: Duration,
+}
+
+/// Parse the input and solve the given part, timing both phases separately
+pub fn time_part(puzzle: &dyn Puzzle, part: usize, lines: Vec<String>) -> Report {
+    let start = Instant::now();
+    let input = puzzle.parse(lines);
+    let parse = start.elapsed();
+
+    let start = Instant::now();
+    let answer = input.and_then(|input| puzzle.solve(part, input.as_ref()));
+    let solve = start.elapsed();
+
+    Report { day: puzzle.day(), part, answer, parse, solve }
+}
+
+/// Read the input of a day and time each of the given parts.
+/// Errors point at the input file; if it can't be read, every part fails with the same error.
+pub fn time_day(puzzle: &dyn Puzzle, parts: &[usize], source: &Source) -> Vec<Report> {
+    let lines = source.read_lines().map_err(|error| Error::io(source, error));
+    parts
+    .iter()
+    .map(|&part| match &lines {
+        Ok(lines) => {
+            let mut report = time_part(puzzle, part, lines.clone());
+            report.answer = report.answer.map_err(|error| error.in_file(source));
+            report
+        },
+        Err(error) => Report {
+            day: puzzle.day(),
+            part,
+            answer: Err(error.clone()),
+            parse: Duration::default(),
+            solve: Duration::default(),
+        },
+    })
+    .collect()
+}
+
+/// Human-friendly duration with a unit that keeps it readable, e.g. 12.3µs or 4.56s
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos() as f64;
+    if nanos < 1e3 {
+        format!("{:.0}ns", nanos)
+    } else if nanos < 1e6 {
+        format!("{:.1}µs", nanos / 1e3)
+    } else if nanos < 1e9 {
+        format!("{:.1}ms", nanos / 1e6)
+    } else {
+        format!("{:.2}s", nanos / 1e9)
+    }
+}
+
+pub fn print_table(reports: &[Report]) {
+    println!("{:>3} {:>4}  {:<60} {:>10} {:>10}", "day", "part", "answer", "parse", "solve");
+    for report in reports {
+        let answer = match &report.answer {
+            Ok(answer) => answer.to_string(),
+            Err(Error::NoSolution) => "no solution".to_string(),
+            Err(_) => "error".to_string(),
+        };
+        println!(
+            "{:>3} {:>4}  {:<60} {:>10} {:>10}",
+            report.day,
+            report.part,
+            answer,
+            format_duration(report.parse),
+            format_duration(report.solve),
+        );
+    }
+    let parse: Duration = reports.iter().map(|report| report.parse).sum();
+    let solve: Duration = reports.iter().map(|report| report.solve).sum();
+    println!(
+        "{:<70} {:>10} {:>10}  ({} overall)",
+        "total",
+        format_duration(parse),
+        format_duration(solve),
+        format_duration(parse + solve),
+    );
+}
+
+/// Print the reports as a JSON array, one object per line.
+/// Numeric answers are JSON numbers and text answers are strings; the error is null unless the status is "error".
+pub fn print_json(reports: &[Report]) {
+    println!("[");
+    for (i, report) in reports.iter().enumerate() {
+        let (status, answer, error) = match &report.answer {
+            Ok(Answer::Number(number)) => ("solved", number.to_string(), "null".to_string()),
+            Ok(Answer::Text(text)) => ("solved", json_string(text), "null".to_string()),
+            Err(Error::NoSolution) => ("no_solution", "null".to_string(), "null".to_string()),
+            Err(error) => ("error", "null".to_string(), json_string(&error.to_string())),
+        };
+        println!(
+            "  {{\"day\": {}, \"part\": {}, \"status\": \"{}\", \"answer\": {}, \"error\": {}, \"parse_ns\": {}, \"solve_ns\": {}}}{}",
+            report.day,
+            report.part,
+            status,
+            answer,
+            error,
+            report.parse.as_nanos(),
+            report.solve.as_nanos(),
+            if i + 1 < reports.len() { "," } else { "" },
+        );
+    }
+    println!("]");
+}
+
+/// Quote and escape a string for JSON
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json_strings() {
+        assert_eq!(r#""abc,def""#, json_string("abc,def"));
+        assert_eq!(r#""a \"b\"\n  | c\\d\u0001""#, json_string("a \"b\"\n  | c\\d\u{1}"));
+    }
+
+}
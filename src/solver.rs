@@ -0,0 +1,179 @@
+use std::any::Any;
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::{day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11, day12, day13, day14, day15, day16, day17,
+    day18, day19, day20, day21, day22, day23, day24, day25};
+
+/// The answer to one part of a puzzle.
+/// Most answers are numbers, but some days (e.g. 21 and 23) ask for a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Number(number) => write!(f, "{}", number),
+            Answer::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+macro_rules! number_answer {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(number: $t) -> Answer {
+                Answer::Number(number as i128)
+            }
+        })*
+    };
+}
+
+number_answer!(u32, u64, i64, usize, isize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Answer {
+        Answer::Text(text)
+    }
+}
+
+/// An example input from the puzzle statement
+pub struct Example {
+    pub input: &'static str,
+    /// Expected answer to each part, None if the example isn't meant for that part
+    pub answers: [Option<&'static str>; 2],
+}
+
+impl Example {
+    pub fn lines(&self) -> Vec<String> {
+        self.input.lines().map(|line| line.to_string()).collect()
+    }
+
+    /// Expected answer to the given part, if any
+    pub fn expected(&self, part: usize) -> Option<&'static str> {
+        self.answers.get(part.checked_sub(1)?).cloned().flatten()
+    }
+}
+
+/// A day of the advent calendar.
+/// The input is parsed once and then handed to each of the parts.
+pub trait Solver {
+    /// Whatever the parts need from the input file
+    type Input: 'static;
+
+    /// Day of the calendar, 1 to 25
+    const DAY: usize;
+
+    /// How many parts have been solved (day 25 only has one)
+    const PARTS: usize = 2;
+
+    /// Examples from the puzzle statement, with their answers
+    const EXAMPLES: &'static [Example] = &[];
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Input>;
+
+    fn part1(&self, input: &Self::Input) -> Result<Answer>;
+
+    /// Only called if `PARTS` is 2
+    fn part2(&self, _input: &Self::Input) -> Result<Answer> {
+        Err(Error::NoSolution)
+    }
+
+    /// Something worth knowing about how a part was solved, to print under its answer
+    fn note(&self, _part: usize, _input: &Self::Input) -> Option<String> {
+        None
+    }
+}
+
+/// Type-erased view of a `Solver`, so that all days can live in the same registry.
+pub trait Puzzle {
+    fn day(&self) -> usize;
+    fn parts(&self) -> usize;
+    fn examples(&self) -> &'static [Example];
+    fn parse(&self, lines: Vec<String>) -> Result<Box<dyn Any>>;
+    /// Panics if `input` was not produced by this same puzzle's `parse`
+    fn solve(&self, part: usize, input: &dyn Any) -> Result<Answer>;
+    /// See `Solver::note`, panics like `solve`
+    fn note(&self, part: usize, input: &dyn Any) -> Option<String>;
+}
+
+impl<S: Solver> Puzzle for S {
+    fn day(&self) -> usize {
+        S::DAY
+    }
+
+    fn parts(&self) -> usize {
+        S::PARTS
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        S::EXAMPLES
+    }
+
+    fn parse(&self, lines: Vec<String>) -> Result<Box<dyn Any>> {
+        Ok(Box::new(Solver::parse(self, lines)?))
+    }
+
+    fn solve(&self, part: usize, input: &dyn Any) -> Result<Answer> {
+        let input = input.downcast_ref::<S::Input>().expect("input parsed by another puzzle");
+        match part {
+            1 => self.part1(input),
+            2 => self.part2(input),
+            _ => Err(Error::NoSolution),
+        }
+    }
+
+    fn note(&self, part: usize, input: &dyn Any) -> Option<String> {
+        Solver::note(self, part, input.downcast_ref::<S::Input>().expect("input parsed by another puzzle"))
+    }
+}
+
+/// All the days solved so far, in order.
+/// Adding a day means adding its solver here.
+pub fn registry() -> Vec<Box<dyn Puzzle>> {
+    vec![
+        Box::new(day1::Day1),
+        Box::new(day2::Day2),
+        Box::new(day3::Day3),
+        Box::new(day4::Day4),
+        Box::new(day5::Day5),
+        Box::new(day6::Day6),
+        Box::new(day7::Day7),
+        Box::new(day8::Day8),
+        Box::new(day9::Day9),
+        Box::new(day10::Day10),
+        Box::new(day11::Day11),
+        Box::new(day12::Day12),
+        Box::new(day13::Day13),
+        Box::new(day14::Day14),
+        Box::new(day15::Day15),
+        Box::new(day16::Day16),
+        Box::new(day17::Day17),
+        Box::new(day18::Day18),
+        Box::new(day19::Day19),
+        Box::new(day20::Day20),
+        Box::new(day21::Day21),
+        Box::new(day22::Day22),
+        Box::new(day23::Day23),
+        Box::new(day24::Day24),
+        Box::new(day25::Day25),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::registry;
+
+    #[test]
+    fn examples_parse() {
+        for puzzle in registry() {
+            for example in puzzle.examples() {
+                assert!(puzzle.parse(example.lines()).is_ok(), "an example of day {} doesn't parse", puzzle.day());
+            }
+        }
+    }
+
+}